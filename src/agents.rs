@@ -1,5 +1,11 @@
-use std::{collections::HashMap, fs};
-use crate::{commands::Command, error::CommonError, package_json::PackageJson, utils};
+use std::{collections::HashMap, fs, path::Path, process};
+use crate::{
+    commands::Command,
+    config::{self, Config},
+    error::CommonError,
+    package_json::{self, PackageJson},
+    utils,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Agent {
@@ -25,10 +31,10 @@ impl From<String> for Agent {
 impl From<Agent> for String {
     fn from(agent: Agent) -> Self {
         match agent {
-            agent if agent == Agent::Npm => "npm".to_string(),
-            agent if agent == Agent::Pnpm => "pnpm".to_string(),
-            agent if agent == Agent::Yarn => "yarn".to_string(),
-            agent if agent == Agent::Bun => "bun".to_string(),
+            Agent::Npm => "npm".to_string(),
+            Agent::Pnpm => "pnpm".to_string(),
+            Agent::Yarn => "yarn".to_string(),
+            Agent::Bun => "bun".to_string(),
             _ => "Not Found".to_string(),
         }
     }
@@ -47,7 +53,11 @@ impl Agent {
                 (Command::Upgrade, Some("npm update $0".to_string())),
                 (Command::UpgradeInteractive, None),
                 (Command::Execute, Some("npx $0".to_string())),
+                (Command::ExecuteLocal, Some("npx $0".to_string())),
                 (Command::Uninstall, Some("npm uninstall $0".to_string())),
+                (Command::PkgView, Some("npm view $0".to_string())),
+                (Command::Import, None),
+                (Command::CleanCache, Some("npm cache clean --force".to_string())),
                 (
                     Command::GlobalUninstall,
                     Some("npm uninstall -g $0".to_string()),
@@ -63,7 +73,11 @@ impl Agent {
                 (Command::Upgrade, None),
                 (Command::UpgradeInteractive, None),
                 (Command::Execute, None),
+                (Command::ExecuteLocal, Some("bun x $0".to_string())),
                 (Command::Uninstall, Some("bun remove $0".to_string())),
+                (Command::PkgView, Some("bun pm view $0".to_string())),
+                (Command::Import, None),
+                (Command::CleanCache, Some("bun pm cache rm".to_string())),
                 (
                     Command::GlobalUninstall,
                     Some("npm remove -g $0".to_string()),
@@ -85,7 +99,11 @@ impl Agent {
                     Some("yarn upgrade-interactive $0".to_string()),
                 ),
                 (Command::Execute, Some("yarn dlx $0".to_string())),
+                (Command::ExecuteLocal, Some("yarn $0".to_string())),
                 (Command::Uninstall, Some("yarn remove $0".to_string())),
+                (Command::PkgView, Some("yarn info $0".to_string())),
+                (Command::Import, Some("yarn import".to_string())),
+                (Command::CleanCache, Some("yarn cache clean".to_string())),
                 (
                     Command::GlobalUninstall,
                     Some("yarn global remove $0".to_string()),
@@ -107,7 +125,11 @@ impl Agent {
                     Some("pnpm update -i $0".to_string()),
                 ),
                 (Command::Execute, Some("pnpm dlx $0".to_string())),
+                (Command::ExecuteLocal, Some("pnpm exec $0".to_string())),
                 (Command::Uninstall, Some("pnpm remove $0".to_string())),
+                (Command::PkgView, Some("pnpm view $0".to_string())),
+                (Command::Import, Some("pnpm import".to_string())),
+                (Command::CleanCache, Some("pnpm store prune".to_string())),
                 (
                     Command::GlobalUninstall,
                     Some("pnpm remove --global $0".to_string()),
@@ -117,6 +139,15 @@ impl Agent {
     }
 }
 
+/// the lockfiles by priority, the first one found decides the agent
+const LOCK_FILES: [(&str, Agent); 5] = [
+    ("bun.lockb", Agent::Bun),
+    ("pnpm-lock.yaml", Agent::Pnpm),
+    ("yarn.lock", Agent::Yarn),
+    ("package-lock.json", Agent::Npm),
+    ("npm-shrinkwrap.json", Agent::Npm),
+];
+
 pub struct Agents {
     pub lock_map: HashMap<String, Agent>,
 }
@@ -124,18 +155,37 @@ pub struct Agents {
 impl Agents {
     pub fn new() -> Agents {
         Agents {
-            lock_map: HashMap::from([
-                ("bun.lockb".to_string(), Agent::Bun),
-                ("pnpm-lock.yaml".to_string(), Agent::Pnpm),
-                ("yarn.lock".to_string(), Agent::Yarn),
-                ("package-lock.json".to_string(), Agent::Npm),
-                ("npm-shrinkwrap.json".to_string(), Agent::Npm),
-            ]),
+            lock_map: LOCK_FILES
+                .iter()
+                .map(|&(file_name, agent)| (file_name.to_string(), agent))
+                .collect(),
         }
     }
+
+    /// the lockfiles in current directory, sorted by name
+    pub fn existing_lock_files(&self) -> Vec<String> {
+        let mut lock_files = self
+            .lock_map
+            .keys()
+            .filter(|file_name| Path::new(file_name).is_file())
+            .cloned()
+            .collect::<Vec<String>>();
+        lock_files.sort();
+        lock_files
+    }
+}
+
+/// the agent of the lockfile with the highest priority among the file names
+pub fn get_lock_file_agent(lock_files: &[String]) -> Option<Agent> {
+    LOCK_FILES
+        .iter()
+        .find(|(file_name, _)| lock_files.iter().any(|f| f == file_name))
+        .map(|&(_, agent)| agent)
 }
 
-pub fn get_current_agent() -> Result<Agent, CommonError> {
+/// the agent of the project, by `packageManager`, the lockfiles, `.rirc` or asking,
+/// the asked one isn't saved in debug mode
+pub fn get_current_agent(debug: bool) -> Result<Agent, CommonError> {
     let package_json = PackageJson::from_path("package.json")?;
 
     let agent = match package_json.package_manager {
@@ -145,23 +195,77 @@ pub fn get_current_agent() -> Result<Agent, CommonError> {
             manager.to_string().into()
         }
         None => {
-            let agents = Agents::new();
-            for (file_name, agent) in agents.lock_map.into_iter() {
-                let is_found = fs::read(&file_name).is_ok();
+            for (file_name, agent) in LOCK_FILES {
+                let is_found = fs::read(file_name).is_ok();
                 if is_found {
                     println!("Current agent is {}", String::from(agent));
                     return Ok(agent);
                 }
             }
-            let agents = vec![Agent::Npm, Agent::Pnpm, Agent::Yarn, Agent::Bun]
+            if let Some(agent) = Config::load()?.agent.map(Agent::from) {
+                if agent != Agent::None {
+                    println!("Current agent is {}", String::from(agent));
+                    return Ok(agent);
+                }
+            }
+            let agents = [Agent::Npm, Agent::Pnpm, Agent::Yarn, Agent::Bun]
                 .iter()
                 .map(|&a| a.into())
                 .collect::<Vec<String>>();
 
-            utils::select_a_choice(&agents, "agent", "Choose the agent")?.into()
+            let agent = utils::select_a_choice(&agents, "agent", "Choose the agent")?.into();
+            remember_agent(agent, debug)?;
+            agent
         }
     };
 
     println!("Current agent is {}", String::from(agent));
     Ok(agent)
 }
+
+/// the version of the installed agent, like `8.6.0`
+pub fn get_version(agent: Agent) -> Option<String> {
+    let name = String::from(agent);
+    let output = match cfg!(target_os = "windows") {
+        true => process::Command::new("cmd")
+            .args(["/C", &name, "--version"])
+            .output(),
+        false => process::Command::new(&name).arg("--version").output(),
+    }
+    .ok()?;
+
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match output.status.success() && !version.is_empty() {
+        true => Some(version),
+        false => None,
+    }
+}
+
+/// save the chosen agent, so it's not asked again in the project
+fn remember_agent(agent: Agent, debug: bool) -> Result<(), CommonError> {
+    let choices = vec![
+        "packageManager - save to package.json".to_string(),
+        ".rirc - save to the config of the project".to_string(),
+        "no - ask again next time".to_string(),
+    ];
+    let answer = utils::select_a_choice(&choices, "remember", "Remember the agent?")?;
+
+    match answer.as_str() {
+        "packageManager" => {
+            let version = get_version(agent).ok_or_else(|| {
+                CommonError::NotFound(format!("{} is not installed", String::from(agent)))
+            })?;
+            let package_manager = format!("{}@{}", String::from(agent), version);
+            println!("{}", package_json::update_package_manager(&package_manager, debug)?);
+        }
+        ".rirc" => {
+            let contents = fs::read_to_string(".rirc").unwrap_or_default();
+            if !debug {
+                fs::write(".rirc", config::set_agent(&contents, &String::from(agent)))?;
+            }
+            println!("agent: {} saved to .rirc", String::from(agent));
+        }
+        _ => {}
+    }
+    Ok(())
+}
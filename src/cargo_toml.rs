@@ -1,7 +1,6 @@
 use crate::error::CommonError;
 use serde::Deserialize;
 use std::{fs, path::Path};
-use toml;
 
 #[derive(Deserialize, Debug)]
 pub struct CargoToml {
@@ -10,15 +9,30 @@ pub struct CargoToml {
 
 #[derive(Deserialize, Debug)]
 struct Package {
+    name: Option<String>,
     homepage: Option<String>,
     repository: Option<String>,
+    documentation: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CargoLock {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
 }
 
 impl CargoToml {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, CommonError> {
         let contents = fs::read_to_string(path)?;
 
-        let cargo_toml: CargoToml = toml::from_str(&contents).unwrap();
+        let cargo_toml: CargoToml = toml::from_str(&contents)?;
 
         Ok(cargo_toml)
     }
@@ -41,6 +55,51 @@ impl CargoToml {
     }
 }
 
+impl CargoToml {
+    pub fn get_name(&self) -> Result<String, CommonError> {
+        match self.package.as_ref().and_then(|pkg| pkg.name.as_ref()) {
+            Some(name) => Ok(name.to_string()),
+            None => Err(CommonError::NotFound(
+                "cargo.toml [package] name field not found!".to_string(),
+            )),
+        }
+    }
+
+    pub fn get_documentation(&self) -> Option<String> {
+        self.package.as_ref()?.documentation.clone()
+    }
+
+    /// unlike `get_url`, the repository field takes precedence over the homepage
+    pub fn get_repository_url(&self) -> Result<String, CommonError> {
+        match self
+            .package
+            .as_ref()
+            .and_then(|pkg| pkg.repository.as_ref())
+        {
+            Some(url) => Ok(url.trim_end_matches(".git").to_string()),
+            None => self.get_url(),
+        }
+    }
+}
+
+impl CargoLock {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, CommonError> {
+        let contents = fs::read_to_string(path)?;
+
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// the locked versions of a crate which is downloaded from a registry
+    pub fn get_registry_versions(&self, name: &str) -> Vec<String> {
+        self.package
+            .iter()
+            .filter(|pkg| pkg.name == name)
+            .filter(|pkg| matches!(&pkg.source, Some(source) if source.starts_with("registry+") || source.starts_with("sparse+")))
+            .map(|pkg| pkg.version.to_string())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,4 +110,23 @@ mod tests {
         let homepage = cargo_toml.get_url().unwrap();
         assert_eq!(homepage, "https://github.com/JiatLn/ri");
     }
+
+    #[test]
+    fn test_cargo_lock() {
+        let cargo_lock: CargoLock = toml::from_str(
+            r#"
+            [[package]]
+            name = "rni"
+            version = "0.3.5"
+
+            [[package]]
+            name = "serde"
+            version = "1.0.147"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(cargo_lock.get_registry_versions("serde"), vec!["1.0.147"]);
+        assert!(cargo_lock.get_registry_versions("rni").is_empty());
+    }
 }
@@ -0,0 +1,241 @@
+use crate::error::CommonError;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+};
+
+/// build outputs of frameworks and bundlers, and `target` of cargo
+pub const BUILD_DIRS: [&str; 5] = ["dist", ".next", ".nuxt", ".output", "target"];
+
+/// caches of tools, and test coverage reports
+pub const CACHE_DIRS: [&str; 3] = [".turbo", ".parcel-cache", "coverage"];
+
+/// find the directories with one of the names under root, found ones are not walked into,
+/// and symlinks are skipped, so nothing outside the project is touched
+///
+/// dependencies are never walked into, and `target` is only matched beside a `Cargo.toml`
+pub fn find_dirs(root: &Path, names: &[&str]) -> Vec<PathBuf> {
+    let mut found = vec![];
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            // `file_type` of an entry doesn't follow symlinks
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if !is_dir {
+                continue;
+            }
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if names.contains(&name.as_ref()) && is_removable(&entry.path()) {
+                found.push(entry.path());
+            } else if name != ".git" && name != "node_modules" {
+                stack.push(entry.path());
+            }
+        }
+    }
+
+    found.sort();
+    found
+}
+
+/// `target` is a common name, only the build output of cargo is removable
+fn is_removable(path: &Path) -> bool {
+    match path.file_name() {
+        Some(name) if name == "target" => path.with_file_name("Cargo.toml").is_file(),
+        _ => true,
+    }
+}
+
+/// the total size of files in the directory, symlinks are not followed
+pub fn dir_size(path: &Path) -> u64 {
+    let mut size = 0;
+    let mut stack = vec![path.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => stack.push(entry.path()),
+                Ok(metadata) if metadata.is_file() => size += metadata.len(),
+                _ => continue,
+            }
+        }
+    }
+
+    size
+}
+
+/// the size of each directory, computed in parallel
+pub fn dirs_sizes(paths: &[PathBuf]) -> Vec<u64> {
+    thread::scope(|scope| {
+        let handles = paths
+            .iter()
+            .map(|path| scope.spawn(move || dir_size(path)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or(0))
+            .collect()
+    })
+}
+
+/// remove directories in parallel, the ones already gone are treated as removed
+pub fn remove_dirs(paths: &[PathBuf]) -> Result<(), CommonError> {
+    // split the directories into their entries, so a single huge node_modules
+    // is removed in parallel too
+    let entries = paths
+        .iter()
+        .filter_map(|path| fs::read_dir(path).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()).map(|e| e.path()))
+        .collect::<Vec<PathBuf>>();
+    let queue = Mutex::new(entries);
+
+    let workers = thread::available_parallelism().map_or(4, |n| n.get());
+    let errors = thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut errors = vec![];
+                    loop {
+                        let path = match queue.lock().unwrap().pop() {
+                            Some(path) => path,
+                            None => break,
+                        };
+                        if let Err(err) = remove_path(&path) {
+                            errors.push(err);
+                        }
+                    }
+                    errors
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect::<Vec<io::Error>>()
+    });
+
+    if let Some(err) = errors.into_iter().next() {
+        return Err(err.into());
+    }
+    for path in paths {
+        ignore_not_found(fs::remove_dir_all(path))?;
+    }
+    Ok(())
+}
+
+/// symlinks are removed themselves, their targets are kept
+fn remove_path(path: &Path) -> io::Result<()> {
+    let is_dir = fs::symlink_metadata(path)
+        .map(|metadata| metadata.is_dir())
+        .unwrap_or(false);
+    match is_dir {
+        true => ignore_not_found(fs::remove_dir_all(path)),
+        false => ignore_not_found(fs::remove_file(path)),
+    }
+}
+
+fn ignore_not_found(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// format bytes to be readable, like `1.5 MB`
+pub fn format_size(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+
+    #[test]
+    fn test_find_and_remove_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for dir in [
+            "node_modules/vite/node_modules/esbuild",
+            "packages/core/node_modules/vue",
+            "packages/utils/src",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("node_modules/vite/index.js"), "12345").unwrap();
+        fs::write(root.join("packages/utils/src/index.js"), "1").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("packages/utils"), root.join("node_modules/utils"))
+            .unwrap();
+
+        let dirs = find_dirs(root, &["node_modules"]);
+        assert_eq!(
+            dirs,
+            vec![
+                root.join("node_modules"),
+                root.join("packages/core/node_modules")
+            ]
+        );
+        assert_eq!(dirs_sizes(&dirs), vec![5, 0]);
+
+        remove_dirs(&dirs).unwrap();
+        assert!(find_dirs(root, &["node_modules"]).is_empty());
+        assert!(root.join("packages/utils/src/index.js").exists());
+
+        // already gone
+        remove_dirs(&dirs).unwrap();
+    }
+
+    #[test]
+    fn test_find_build_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for dir in [
+            "dist",
+            "node_modules/vite/dist",
+            "packages/core/dist",
+            "packages/core/src/target",
+            "crates/cli/target",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("crates/cli/Cargo.toml"), "").unwrap();
+
+        assert_eq!(
+            find_dirs(root, &BUILD_DIRS),
+            vec![
+                root.join("crates/cli/target"),
+                root.join("dist"),
+                root.join("packages/core/dist")
+            ]
+        );
+    }
+}
@@ -4,6 +4,9 @@ pub enum Command {
     Add,
     Install,
     Run,
+    RunScripts,
+    RunScriptsParallel,
+    ListScripts,
     Unkown,
     Frozen,
     Global,
@@ -11,14 +14,26 @@ pub enum Command {
     Uninstall,
     GlobalUninstall,
     Execute,
+    ExecuteLocal,
     UpgradeInteractive,
-    IgnoredCommand,
+    Ignored,
     GitClone,
     GitPull,
     GitPush,
     GitLog,
+    GitCheckout,
+    GitBranch,
+    GitBranchDelete,
+    GitCommit,
     RemoveNodeModules,
+    RemoveBuildFiles,
     RemoveLockFile,
+    CleanCache,
+    Import,
+    Migrate,
+    UseAgent,
     PkgInfo,
+    PkgView,
+    CrateInfo,
     PkgRepo,
 }
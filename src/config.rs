@@ -0,0 +1,157 @@
+use crate::error::CommonError;
+use serde::Deserialize;
+use std::{env, fs, path::PathBuf};
+
+/// The config of ri, read from `.rirc` of current directory and `~/.rirc`
+///
+/// ```toml
+/// agent = "pnpm"
+///
+/// [git]
+/// pull_rebase = true
+/// pull_install = "auto"
+///
+/// [clean]
+/// build = ["dist", "build"]
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub struct Config {
+    /// the agent of projects without lockfile and `packageManager`
+    pub agent: Option<String>,
+
+    #[serde(default)]
+    pub git: GitConfig,
+
+    #[serde(default)]
+    pub clean: CleanConfig,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct GitConfig {
+    /// `ri pl` runs `git pull --rebase` by default
+    pub pull_rebase: Option<bool>,
+
+    /// install dependencies when `ri pl` changed the lockfile or package.json
+    pub pull_install: Option<InstallMode>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct CleanConfig {
+    /// directories removed by `ri rm --build`, defaults to `clean::BUILD_DIRS`
+    pub build: Option<Vec<String>>,
+
+    /// directories removed by `ri rm --cache`, defaults to `clean::CACHE_DIRS`
+    pub cache: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    Auto,
+    #[default]
+    Prompt,
+    Never,
+}
+
+impl Config {
+    /// fields of the project config take precedence over the global one
+    pub fn load() -> Result<Config, CommonError> {
+        let project = Config::from_path(PathBuf::from(".rirc"))?;
+        let global = match home_dir() {
+            Some(home) => Config::from_path(home.join(".rirc"))?,
+            None => Config::default(),
+        };
+        Ok(project.merge(global))
+    }
+
+    fn from_path(path: PathBuf) -> Result<Config, CommonError> {
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|err| CommonError::TomlParseError(format!("{}: {}", path.display(), err))),
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    fn merge(self, other: Config) -> Config {
+        Config {
+            agent: self.agent.or(other.agent),
+            git: GitConfig {
+                pull_rebase: self.git.pull_rebase.or(other.git.pull_rebase),
+                pull_install: self.git.pull_install.or(other.git.pull_install),
+            },
+            clean: CleanConfig {
+                build: self.clean.build.or(other.clean.build),
+                cache: self.clean.cache.or(other.clean.cache),
+            },
+        }
+    }
+}
+
+/// set `agent` of the config contents, the other lines are kept
+pub fn set_agent(contents: &str, agent: &str) -> String {
+    let line = format!("agent = \"{}\"", agent);
+    // keys before the first table are the top level ones
+    let top_level = contents
+        .lines()
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .collect::<Vec<&str>>();
+
+    match top_level
+        .iter()
+        .position(|l| l.split('=').next().unwrap_or_default().trim() == "agent")
+    {
+        Some(index) => {
+            let mut lines = contents.lines().collect::<Vec<&str>>();
+            lines[index] = &line;
+            lines.join("\n") + "\n"
+        }
+        None if contents.trim().is_empty() => line + "\n",
+        None => format!("{}\n\n{}", line, contents),
+    }
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_config() {
+        let project: Config = toml::from_str("").unwrap();
+        let global: Config = toml::from_str("[git]\npull_rebase = true").unwrap();
+        assert_eq!(project.merge(global).git.pull_rebase, Some(true));
+
+        let project: Config = toml::from_str("[git]\npull_rebase = false").unwrap();
+        let global: Config = toml::from_str("[git]\npull_rebase = true").unwrap();
+        assert_eq!(project.merge(global).git.pull_rebase, Some(false));
+
+        let project: Config = toml::from_str("[git]\npull_install = \"never\"").unwrap();
+        assert_eq!(
+            project.merge(Config::default()).git.pull_install,
+            Some(InstallMode::Never)
+        );
+    }
+
+    #[test]
+    fn test_set_agent() {
+        assert_eq!(set_agent("", "pnpm"), "agent = \"pnpm\"\n");
+        assert_eq!(
+            set_agent("[git]\npull_rebase = true\n", "pnpm"),
+            "agent = \"pnpm\"\n\n[git]\npull_rebase = true\n"
+        );
+        assert_eq!(
+            set_agent("agent = \"npm\"\n\n[git]\nagent = 1\n", "yarn"),
+            "agent = \"yarn\"\n\n[git]\nagent = 1\n"
+        );
+
+        let config: Config =
+            toml::from_str(&set_agent("[git]\npull_rebase = true", "bun")).unwrap();
+        assert_eq!(config.agent, Some("bun".to_string()));
+        assert_eq!(config.git.pull_rebase, Some(true));
+    }
+}
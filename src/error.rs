@@ -7,7 +7,12 @@ use std::{
 pub enum CommonError {
     NotFound(String),
     JsonParseError(String),
+    TomlParseError(String),
     Termination(String),
+    Unsupported(String),
+    ExecuteFailed(String),
+    ClipboardError(String),
+    NotInteractive(String),
     Uninitialized,
 }
 
@@ -16,7 +21,12 @@ impl Display for CommonError {
         match self {
             Self::NotFound(str) => write!(f, "NotFoundError: {}", str),
             Self::JsonParseError(str) => write!(f, "JsonParserError: {}", str),
+            Self::TomlParseError(str) => write!(f, "TomlParserError: {}", str),
             Self::Termination(str) => write!(f, "Termination: {}", str),
+            Self::Unsupported(str) => write!(f, "UnsupportedError: {}", str),
+            Self::ExecuteFailed(str) => write!(f, "ExecuteError: {}", str),
+            Self::ClipboardError(str) => write!(f, "ClipboardError: {}", str),
+            Self::NotInteractive(str) => write!(f, "NotInteractiveError: {}", str),
             Self::Uninitialized => write!(f, "Uninitialized Error"),
         }
     }
@@ -27,7 +37,12 @@ impl Error for CommonError {
         match self {
             Self::NotFound(_) => None,
             Self::JsonParseError(_) => None,
+            Self::TomlParseError(_) => None,
             Self::Termination(_) => None,
+            Self::Unsupported(_) => None,
+            Self::ExecuteFailed(_) => None,
+            Self::ClipboardError(_) => None,
+            Self::NotInteractive(_) => None,
             Self::Uninitialized => None,
         }
     }
@@ -51,6 +66,12 @@ impl From<serde_json::Error> for CommonError {
     }
 }
 
+impl From<toml::de::Error> for CommonError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::TomlParseError(err.to_string())
+    }
+}
+
 impl From<requestty::ErrorKind> for CommonError {
     fn from(err: requestty::ErrorKind) -> Self {
         match err {
@@ -0,0 +1,63 @@
+use std::process::Command;
+
+/// run git and capture the trimmed stdout, `None` if git fails
+pub fn output(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    match output.status.success() {
+        true => Some(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+        false => None,
+    }
+}
+
+pub fn current_branch() -> Option<String> {
+    output(&["symbolic-ref", "--short", "HEAD"])
+}
+
+pub fn has_upstream() -> bool {
+    output(&["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"]).is_some()
+}
+
+/// the remote to push a new branch to, `remote.pushDefault` or `origin` or the only remote
+pub fn push_remote() -> Option<String> {
+    if let Some(remote) = output(&["config", "--get", "remote.pushDefault"]) {
+        return Some(remote);
+    }
+    let remotes = output(&["remote"])?;
+    let remotes = remotes.lines().collect::<Vec<&str>>();
+    match remotes.contains(&"origin") {
+        true => Some("origin".to_string()),
+        false => remotes.first().map(|remote| remote.to_string()),
+    }
+}
+
+/// local branches except the current one, the recently committed first,
+/// like `main - 2 days ago`
+pub fn recent_branches() -> Vec<String> {
+    let branches = output(&[
+        "for-each-ref",
+        "--sort=-committerdate",
+        "--format=%(HEAD)%(refname:short) - %(committerdate:relative)",
+        "refs/heads",
+    ])
+    .unwrap_or_default();
+
+    branches
+        .lines()
+        .filter(|line| !line.starts_with('*'))
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+/// local branches merged into the current one, except the current and the protected ones
+pub fn merged_branches() -> Vec<String> {
+    // `--merged` takes the next arg as a commit, so it goes last
+    let branches =
+        output(&["branch", "--format=%(HEAD)%(refname:short)", "--merged"]).unwrap_or_default();
+
+    branches
+        .lines()
+        .filter(|line| !line.starts_with('*'))
+        .map(|line| line.trim().to_string())
+        .filter(|branch| !["main", "master", "develop", "dev"].contains(&branch.as_str()))
+        .collect()
+}
@@ -3,22 +3,32 @@ use structopt::StructOpt;
 
 mod agents;
 mod cargo_toml;
+mod clean;
 mod commands;
+mod config;
 mod error;
+mod git;
+mod migrate;
+mod opener;
 mod opt;
 mod package_json;
 mod parser;
+mod paste;
+mod repo;
 mod runner;
+mod scripts;
 mod utils;
+mod workspace;
 
 fn main() -> Result<(), CommonError> {
     let opt = opt::Opt::from_args();
+    utils::init_interaction(opt.yes, opt.no_interactive);
 
     let mut parser = parser::Parser::parser_opt(&opt)?;
 
     let cmd = parser.gene_command(&opt)?;
 
-    if cmd.len() > 0 {
+    if !cmd.is_empty() {
         println!("Execute: {}", &cmd);
     }
 
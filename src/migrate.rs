@@ -0,0 +1,113 @@
+use crate::{
+    agents::{Agent, Agents},
+    commands::Command,
+    error::CommonError,
+    package_json,
+    runner::Runner,
+    utils,
+};
+use std::fs;
+
+/// the command to import the lockfile of agent by target of the version, if target can read it
+///
+/// bun.lockb is binary, `yarn import` only reads package-lock.json, and yarn 2+ has no import
+pub fn import_command(agent: Agent, target: Agent, version: &str) -> Option<String> {
+    match (agent, target) {
+        (Agent::Bun, _) | (Agent::Pnpm, Agent::Yarn) => None,
+        (_, Agent::Yarn) if !version.starts_with("1.") => None,
+        _ => Agent::get_agent_hash_map(target)
+            .remove(&Command::Import)
+            .flatten(),
+    }
+}
+
+/// migrate the project from agent to target of the version, returns the changes made
+///
+/// `packageManager` is set before anything runs, corepack refuses to run an agent
+/// other than the one in it, and the old lockfile is removed after the install, if a
+/// step fails, package.json and the lockfiles are put back
+pub fn migrate(
+    agent: Agent,
+    target: Agent,
+    version: &str,
+    debug: bool,
+) -> Result<Vec<String>, CommonError> {
+    let lock_map = Agents::new().lock_map;
+    let lock_files_of = |agent: Agent| {
+        Agents::new()
+            .existing_lock_files()
+            .into_iter()
+            .filter(|file_name| lock_map.get(file_name) == Some(&agent))
+            .collect::<Vec<String>>()
+    };
+    let lock_files = lock_files_of(agent);
+    let import = match lock_files.is_empty() {
+        true => None,
+        false => import_command(agent, target, version),
+    };
+    let install = Agent::get_agent_hash_map(target)
+        .remove(&Command::Install)
+        .flatten()
+        .unwrap_or_default()
+        .replace("$0", "")
+        .trim()
+        .to_string();
+
+    let original = fs::read_to_string("package.json")?;
+    let target_lock_files = lock_files_of(target);
+    let package_manager = format!("{}@{}", String::from(target), version);
+    let change = package_json::update_package_manager(&package_manager, debug)?;
+    let mut changes = vec![change];
+
+    let commands = import.iter().chain([&install]);
+    for command in commands {
+        println!("Execute: {}", command);
+        if debug {
+            continue;
+        }
+        if let Err(err) = Runner::run(command) {
+            fs::write("package.json", &original)?;
+            let generated = lock_files_of(target)
+                .into_iter()
+                .filter(|file_name| !target_lock_files.contains(file_name))
+                .collect::<Vec<String>>();
+            utils::remove_lock_files(&generated)?;
+            return Err(err);
+        }
+    }
+    if let Some(import) = import {
+        changes.push(format!("lockfile imported by `{}`", import));
+    }
+
+    if !debug {
+        utils::remove_lock_files(&lock_files)?;
+    }
+    for file_name in &lock_files {
+        changes.push(format!("{} removed", file_name));
+    }
+    for file_name in lock_files_of(target) {
+        changes.push(format!("{} generated", file_name));
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_command() {
+        assert_eq!(
+            import_command(Agent::Npm, Agent::Pnpm, "8.6.0"),
+            Some("pnpm import".to_string())
+        );
+        assert_eq!(
+            import_command(Agent::Npm, Agent::Yarn, "1.22.19"),
+            Some("yarn import".to_string())
+        );
+        assert_eq!(import_command(Agent::Npm, Agent::Yarn, "4.0.2"), None);
+        assert_eq!(import_command(Agent::Pnpm, Agent::Yarn, "1.22.19"), None);
+        assert_eq!(import_command(Agent::Bun, Agent::Npm, "10.0.0"), None);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::CommonError;
+use std::{env, fmt::Display, process::Command};
+
+/// The program used to open an url in the browser
+#[derive(Debug, PartialEq, Eq)]
+pub struct Opener {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Opener {
+    pub fn new<S: Into<String>>(program: S) -> Opener {
+        Opener {
+            program: program.into(),
+            args: vec![],
+        }
+    }
+
+    /// `$BROWSER` takes precedence over the default opener of current platform
+    pub fn from_env() -> Opener {
+        match env::var("BROWSER") {
+            Ok(browser) if !browser.trim().is_empty() => Opener::from_browser(&browser),
+            _ => Opener::platform_default(),
+        }
+    }
+
+    /// parse `$BROWSER`, such as `firefox` or `firefox --new-tab %s`
+    fn from_browser(browser: &str) -> Opener {
+        let mut parts = browser.split_whitespace().map(String::from);
+        Opener {
+            program: parts.next().unwrap_or_default(),
+            args: parts.collect(),
+        }
+    }
+
+    fn platform_default() -> Opener {
+        if cfg!(target_os = "windows") {
+            // `start` is a builtin of cmd, the empty string is the window title
+            Opener {
+                program: "cmd".to_string(),
+                args: vec!["/C".to_string(), "start".to_string(), "".to_string()],
+            }
+        } else if cfg!(target_os = "macos") {
+            Opener::new("open")
+        } else {
+            Opener::new("xdg-open")
+        }
+    }
+
+    /// the url replaces `%s` if present, otherwise it is appended
+    fn args_with_url(&self, url: &str) -> Vec<String> {
+        let mut args = self.args.clone();
+        if args.iter().any(|arg| arg.contains("%s")) {
+            args.iter_mut()
+                .for_each(|arg| *arg = arg.replace("%s", url));
+        } else {
+            args.push(url.to_string());
+        }
+        args
+    }
+
+    pub fn open(&self, url: &str) -> Result<(), CommonError> {
+        let status = Command::new(&self.program)
+            .args(self.args_with_url(url))
+            .status()
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::NotFound => {
+                    CommonError::NotFound(format!("opener `{}` not found", self.program))
+                }
+                _ => err.into(),
+            })?;
+
+        match status.success() {
+            true => Ok(()),
+            false => Err(CommonError::ExecuteFailed(format!(
+                "`{}` exited with {}",
+                self.program, status
+            ))),
+        }
+    }
+}
+
+impl Display for Opener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_args() {
+        let opener = Opener::from_browser("firefox --new-tab %s");
+        assert_eq!(opener.program, "firefox");
+        assert_eq!(
+            opener.args_with_url("https://github.com"),
+            vec!["--new-tab", "https://github.com"]
+        );
+
+        let opener = Opener::from_browser("w3m");
+        assert_eq!(
+            opener.args_with_url("https://github.com"),
+            vec!["https://github.com"]
+        );
+    }
+
+    #[cfg(unix)]
+    fn stub_opener(dir: &std::path::Path, script: &str) -> Opener {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("opener");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Opener::new(path.to_str().unwrap())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_with_stub() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("opened");
+        let opener = stub_opener(
+            dir.path(),
+            &format!("echo \"$@\" > {}", output.to_str().unwrap()),
+        );
+
+        opener.open("https://github.com/JiatLn/ri").unwrap();
+
+        let opened = std::fs::read_to_string(output).unwrap();
+        assert_eq!(opened.trim(), "https://github.com/JiatLn/ri");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let opener = stub_opener(dir.path(), "exit 3");
+
+        assert!(matches!(
+            opener.open("https://github.com/JiatLn/ri"),
+            Err(CommonError::ExecuteFailed(_))
+        ));
+        assert!(matches!(
+            Opener::new("ri-opener-not-exist").open("https://github.com/JiatLn/ri"),
+            Err(CommonError::NotFound(_))
+        ));
+    }
+}
@@ -1,4 +1,5 @@
-use structopt::StructOpt;
+use crate::repo::RepoTarget;
+use structopt::{clap::AppSettings, StructOpt};
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "Ri", about = "A rust version ni.", rename_all = "kebab-case")]
@@ -15,6 +16,15 @@ pub struct Opt {
 
     #[structopt(short, long)]
     pub global: bool,
+
+    /// Answer yes to all confirms without prompting
+    #[structopt(short, long, global = true)]
+    pub yes: bool,
+
+    /// Never prompt, confirms take their default answer and choices fail,
+    /// it's on when stdin isn't a terminal
+    #[structopt(long, global = true)]
+    pub no_interactive: bool,
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -22,35 +32,178 @@ pub enum SubCommand {
     /// Uninstall package
     Un { package_name: Vec<String> },
 
-    /// Run script
-    R { run_name: Option<String> },
-
-    /// Special for `run dev`
-    Rd,
+    /// Run script, the rest args are passed to the script, `-` runs the last script again,
+    /// and several scripts like `ri r lint test` run in order
+    #[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+    R {
+        /// Run the scripts at the same time, like `ri r -p dev:api dev:web`
+        #[structopt(short, long)]
+        parallel: bool,
 
-    /// Remove node_modules
-    Rm,
+        /// List the scripts with their commands and descriptions, workspaces included
+        #[structopt(short, long)]
+        list: bool,
 
-    /// Remove lockfile
-    Rl,
+        /// Print the list as JSON
+        #[structopt(long, requires = "list")]
+        json: bool,
 
-    /// Git clone
-    Cl { src: Option<String> },
+        run_name: Option<String>,
+        args: Vec<String>,
+    },
 
-    /// Git pull
-    Pl,
+    /// Special for `run dev`
+    Rd,
 
-    /// Git push
-    Ps,
+    /// Run the last `ri r` again, scripts or a binary with their args, like `ri r -`
+    Rr,
+
+    /// Remove node_modules, or build outputs and caches
+    Rm {
+        /// Remove every node_modules under current directory, like the ones of workspaces
+        #[structopt(short, long)]
+        recursive: bool,
+
+        /// Remove build outputs, like dist, .next and target, see `clean.build` of `.rirc`
+        #[structopt(long)]
+        build: bool,
+
+        /// Remove caches, like .turbo and coverage, see `clean.cache` of `.rirc`
+        #[structopt(long)]
+        cache: bool,
+
+        /// Remove every node_modules, build outputs and caches
+        #[structopt(long)]
+        all: bool,
+
+        /// Clear the global cache of current agent, like `pnpm store prune`
+        #[structopt(long, conflicts_with_all = &["recursive", "build", "cache", "all", "dry-run"])]
+        store: bool,
+
+        /// Only list the directories to remove with their sizes
+        #[structopt(long)]
+        dry_run: bool,
+    },
+
+    /// Remove lockfile of current agent, and install to regenerate it
+    Rl {
+        /// Remove lockfiles of all agents
+        #[structopt(long)]
+        all: bool,
+
+        /// Install without confirm after the lockfile is removed
+        #[structopt(short, long)]
+        install: bool,
+    },
+
+    /// Git clone, the url is read from the clipboard if omitted
+    Cl {
+        /// Url of the repository, or shorthand like `user/repo` and `github:user/repo`
+        src: Option<String>,
+
+        /// The directory to clone into
+        dir: Option<String>,
+
+        /// Create a shallow clone with the history truncated to the number of commits
+        #[structopt(long)]
+        depth: Option<u32>,
+
+        /// Install dependencies in the cloned directory
+        #[structopt(short, long)]
+        install: bool,
+    },
+
+    /// Git pull, the rest args are passed to git
+    #[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+    Pl {
+        /// Rebase on top of the upstream, defaults to `git.pull_rebase` of `.rirc`
+        #[structopt(long)]
+        rebase: bool,
+
+        /// Merge the upstream even if `git.pull_rebase` of `.rirc` is true
+        #[structopt(long, conflicts_with = "rebase")]
+        no_rebase: bool,
+
+        /// Install without confirm if the lockfile or package.json is changed
+        #[structopt(short, long)]
+        install: bool,
+
+        /// Never install after pull
+        #[structopt(long, conflicts_with = "install")]
+        no_install: bool,
+
+        args: Vec<String>,
+    },
+
+    /// Git push, the upstream is set on the first push of a new branch
+    #[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+    Ps { args: Vec<String> },
+
+    /// Git log, the rest args like paths are passed to git
+    #[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+    Log {
+        /// Limit the number of commits to show
+        #[structopt(short = "n", long)]
+        max_count: Option<usize>,
+
+        args: Vec<String>,
+    },
+
+    /// Git checkout, pick a branch interactively if omitted
+    Co {
+        /// Create a new branch
+        #[structopt(short = "b")]
+        create: bool,
+
+        branch: Option<String>,
+    },
+
+    /// Git branch
+    Br {
+        /// Delete the branches merged into the current one
+        #[structopt(short, long)]
+        delete: bool,
+    },
+
+    /// Git commit with a conventional commit message, like `feat(core): add something`
+    Cm {
+        /// Stage all changes before commit
+        #[structopt(short, long)]
+        all: bool,
+    },
+
+    /// Set `packageManager` of package.json, the installed version is used if omitted
+    Use {
+        /// like pnpm or pnpm@8.6.0
+        agent: String,
+    },
+
+    /// Migrate to another agent, the lockfile is imported if the agent supports
+    Migrate {
+        /// npm, yarn, pnpm or bun
+        agent: String,
+    },
+
+    /// Get package info, or query the registry for the given package
+    Info {
+        package_name: Option<String>,
+
+        /// Only show these fields, like `version` or `dist-tags`
+        fields: Vec<String>,
+    },
 
-    /// Git log
-    Log,
+    /// Open the browser to the package's repository
+    Repo {
+        /// A dependency, or the target page of current package
+        name: Option<String>,
 
-    /// Get package info
-    Info,
+        /// One of issues, pulls, actions, npm, docs, homepage
+        target: Option<RepoTarget>,
 
-    /// Open the browser to the package's repository
-    Repo,
+        /// Only print the url instead of opening the browser
+        #[structopt(long)]
+        print: bool,
+    },
 
     #[structopt(external_subcommand)]
     Other(Vec<String>),
@@ -1,4 +1,4 @@
-use crate::error::CommonError;
+use crate::{error::CommonError, repo::Host};
 use serde::Deserialize;
 use std::{collections::HashMap, fs, io::BufReader, path::Path};
 
@@ -6,11 +6,22 @@ use std::{collections::HashMap, fs, io::BufReader, path::Path};
 pub struct PackageJson {
     pub name: Option<String>,
     pub version: Option<String>,
+    pub homepage: Option<String>,
     pub repository: Option<PkgRepo>,
+    pub bugs: Option<PkgBugs>,
     pub scripts: Option<HashMap<String, String>>,
 
+    /// descriptions of scripts, the convention of npm-scripts-info
+    #[serde(rename = "scripts-info")]
+    pub scripts_info: Option<HashMap<String, String>>,
+
+    /// descriptions of scripts of ntl
+    pub ntl: Option<PkgNtl>,
+
     #[serde(rename = "packageManager")]
     pub package_manager: Option<String>,
+
+    pub workspaces: Option<PkgWorkspaces>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -20,6 +31,26 @@ pub enum PkgRepo {
     HashMap(HashMap<String, String>),
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum PkgBugs {
+    String(String),
+    HashMap(HashMap<String, String>),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PkgNtl {
+    pub descriptions: Option<HashMap<String, String>>,
+}
+
+/// `["packages/*"]`, or `{ "packages": ["packages/*"] }` of yarn
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum PkgWorkspaces {
+    Array(Vec<String>),
+    Object { packages: Vec<String> },
+}
+
 impl PackageJson {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, CommonError> {
         let file = fs::File::open(path)?;
@@ -32,12 +63,46 @@ impl PackageJson {
         Ok(pkg_json)
     }
 
+    /// the repository url, linked to the `directory` of the package in monorepo
     pub fn get_url(&self) -> Result<String, CommonError> {
+        self.get_repository_url(true)
+    }
+
+    /// the repository url, without the `directory` of the package in monorepo
+    pub fn get_root_url(&self) -> Result<String, CommonError> {
+        self.get_repository_url(false)
+    }
+
+    /// the description of the script from `scripts-info` or `ntl.descriptions`
+    pub fn get_script_description(&self, name: &str) -> Option<String> {
+        let ntl = self.ntl.as_ref().and_then(|ntl| ntl.descriptions.as_ref());
+        [self.scripts_info.as_ref(), ntl]
+            .into_iter()
+            .flatten()
+            .find_map(|descriptions| descriptions.get(name))
+            .map(|description| description.to_string())
+    }
+
+    /// the `bugs` field, ignore the one only contains an email
+    pub fn get_bugs_url(&self) -> Option<String> {
+        match &self.bugs {
+            Some(PkgBugs::String(url)) => Some(url.to_string()),
+            Some(PkgBugs::HashMap(bugs)) => bugs.get("url").map(|url| url.to_string()),
+            None => None,
+        }
+    }
+
+    fn get_repository_url(&self, with_directory: bool) -> Result<String, CommonError> {
         match &self.repository {
             Some(repo) => match repo {
-                PkgRepo::String(repository) => parse_url(repository),
+                PkgRepo::String(repository) => parse_url(repository, None),
                 PkgRepo::HashMap(repo) => match repo.get("url") {
-                    Some(url) => parse_url(url),
+                    Some(url) => parse_url(
+                        url,
+                        repo.get("directory")
+                            .filter(|_| with_directory)
+                            .map(|d| d.as_str()),
+                    ),
                     None => Err(CommonError::NotFound(
                         "package.json repository url field not found!".to_string(),
                     )),
@@ -50,22 +115,193 @@ impl PackageJson {
     }
 }
 
-fn parse_url(url: &str) -> Result<String, CommonError> {
-    let mut url = url.to_string();
-    // TODO: use regex
-    if url.starts_with("git+") {
-        url = url.replace("git+", "");
-    }
-    if url.ends_with(".git") {
-        url = url.replace(".git", "");
-    }
-    // TODO: validate url
+/// parse the repository url to a browsable url
+///
+/// see https://docs.npmjs.com/cli/v9/configuring-npm/package-json#repository
+fn parse_url(url: &str, directory: Option<&str>) -> Result<String, CommonError> {
+    let url = url.trim();
     if url.is_empty() {
         return Err(CommonError::NotFound(
             "package.json repository url field is empty!".to_string(),
         ));
     }
-    Ok(url)
+
+    let repo_url = normalize_url(url).ok_or(CommonError::NotFound(format!(
+        "package.json repository url `{}` is invalid!",
+        url
+    )))?;
+
+    match directory.map(|dir| dir.trim_matches('/')) {
+        Some(dir) if !dir.is_empty() => Ok(format!(
+            "{}/{}",
+            repo_url,
+            Host::from_url(&repo_url).tree_path(dir)
+        )),
+        _ => Ok(repo_url),
+    }
+}
+
+/// such as `git+https://github.com/user/repo.git`, `git@github.com:user/repo.git`,
+/// `ssh://git@github.com/user/repo.git`, `github:user/repo` or `user/repo`
+fn normalize_url(url: &str) -> Option<String> {
+    if url.contains(char::is_whitespace) {
+        return None;
+    }
+
+    // drop the committish, like `github:user/repo#main`
+    let url = url.split('#').next()?;
+    let url = url.strip_prefix("git+").unwrap_or(url);
+
+    let (scheme, host, path) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+            // drop the user info, like `git@` or `user:token@`
+            let host = authority.rsplit('@').next()?;
+            match scheme {
+                "http" | "https" => (scheme, host, path),
+                // the port of ssh is useless in the browser
+                _ => ("https", host.split(':').next()?, path),
+            }
+        }
+        None => match url.split_once(':') {
+            Some(("github", path)) => ("https", "github.com", path),
+            Some(("gitlab", path)) => ("https", "gitlab.com", path),
+            Some(("bitbucket", path)) => ("https", "bitbucket.org", path),
+            Some(("gist", path)) => ("https", "gist.github.com", path),
+            // scp-like syntax, like `git@github.com:user/repo.git`
+            Some((authority, path)) if authority.contains('@') || authority.contains('.') => {
+                ("https", authority.rsplit('@').next()?, path)
+            }
+            Some(_) => return None,
+            None if url.split('/').count() == 2 => ("https", "github.com", url),
+            None => return None,
+        },
+    };
+
+    let path = path.trim_matches('/');
+    let path = path
+        .strip_suffix(".git")
+        .unwrap_or(path)
+        .trim_end_matches('/');
+
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}://{}/{}", scheme, host, path))
+}
+
+/// set `packageManager` of package.json, returns the change like `packageManager: a -> b`
+pub fn update_package_manager(package_manager: &str, debug: bool) -> Result<String, CommonError> {
+    let previous = PackageJson::from_path("package.json")?.package_manager;
+    let contents = fs::read_to_string("package.json")?;
+    let contents = set_package_manager(&contents, package_manager)
+        .ok_or_else(|| CommonError::JsonParseError("package.json is invalid".to_string()))?;
+    if !debug {
+        fs::write("package.json", contents)?;
+    }
+    Ok(format!(
+        "packageManager: {} -> {}",
+        previous.unwrap_or_else(|| "none".to_string()),
+        package_manager
+    ))
+}
+
+/// set `packageManager` of the package.json contents, the other fields and the format are kept,
+/// None if the contents or the result isn't valid json
+pub fn set_package_manager(contents: &str, package_manager: &str) -> Option<String> {
+    let field = format!("\"packageManager\": \"{}\"", package_manager);
+
+    let contents = match find_field(contents, "packageManager") {
+        Some(start) => {
+            let value = contents[start..].split_once(':')?.1.trim_start();
+            let value_start = contents.len() - value.len();
+            if !value.starts_with('"') {
+                return None;
+            }
+            let value_end = string_end(contents, value_start)?;
+            format!(
+                "{}{}{}",
+                &contents[..start],
+                field,
+                &contents[value_end + 1..]
+            )
+        }
+        None => {
+            // append it as the last field, indented like the first one
+            let end = contents.rfind('}')?;
+            let last = contents[..end].trim_end();
+            let indent = contents
+                .lines()
+                .skip(1)
+                .find(|line| line.trim_start().starts_with('"'))
+                .map(|line| &line[..line.len() - line.trim_start().len()])
+                .unwrap_or("  ");
+            let separator = match last.ends_with('{') {
+                true => "",
+                false => ",",
+            };
+            let newline = match contents.contains("\r\n") {
+                true => "\r\n",
+                false => "\n",
+            };
+            format!(
+                "{}{}{}{}{}{}{}",
+                last,
+                separator,
+                newline,
+                indent,
+                field,
+                newline,
+                &contents[end..]
+            )
+        }
+    };
+
+    serde_json::from_str::<serde_json::Value>(&contents).ok()?;
+    Some(contents)
+}
+
+/// the start of the key of a field in the top-level object, the nested objects and
+/// the strings are skipped
+fn find_field(contents: &str, key: &str) -> Option<usize> {
+    let bytes = contents.as_bytes();
+    let mut depth = 0;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'"' => {
+                let end = string_end(contents, index)?;
+                let is_key = contents[end + 1..].trim_start().starts_with(':');
+                if depth == 1 && is_key && contents[index + 1..end] == *key {
+                    return Some(index);
+                }
+                index = end;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+/// the index of the quote closing the string which starts at start
+fn string_end(contents: &str, start: usize) -> Option<usize> {
+    let bytes = contents.as_bytes();
+    let mut index = start + 1;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 1,
+            b'"' => return Some(index),
+            _ => {}
+        }
+        index += 1;
+    }
+    None
 }
 
 #[cfg(test)]
@@ -75,6 +311,171 @@ mod tests {
     #[test]
     fn test_read_package_json() {
         let result = PackageJson::from_path("tests/package.json");
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().get_url().unwrap(),
+            "https://github.com/JiatLn/ri"
+        );
+    }
+
+    #[test]
+    fn test_get_script_description() {
+        let package_json = PackageJson::from_path("tests/package.json").unwrap();
+        assert_eq!(
+            package_json.get_script_description("dev").unwrap(),
+            "start the dev server"
+        );
+        assert_eq!(
+            package_json.get_script_description("build").unwrap(),
+            "build for production"
+        );
+        assert_eq!(package_json.get_script_description("test"), None);
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let expected = "https://github.com/JiatLn/ri";
+        for url in [
+            "https://github.com/JiatLn/ri",
+            "https://github.com/JiatLn/ri.git",
+            "https://github.com/JiatLn/ri/",
+            "git+https://github.com/JiatLn/ri.git",
+            "git+https://user@github.com/JiatLn/ri.git",
+            "git://github.com/JiatLn/ri.git",
+            "git+ssh://git@github.com/JiatLn/ri.git",
+            "ssh://git@github.com:22/JiatLn/ri.git",
+            "git@github.com:JiatLn/ri.git",
+            "github:JiatLn/ri",
+            "github:JiatLn/ri#main",
+            "JiatLn/ri",
+            " JiatLn/ri ",
+        ] {
+            assert_eq!(parse_url(url, None).unwrap(), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_url_keep_dot_git_inside() {
+        assert_eq!(
+            parse_url("https://github.com/user/user.github.io.git", None).unwrap(),
+            "https://github.com/user/user.github.io"
+        );
+        assert_eq!(
+            parse_url("git@github.com:user/.github.git", None).unwrap(),
+            "https://github.com/user/.github"
+        );
+    }
+
+    #[test]
+    fn test_parse_url_other_hosts() {
+        assert_eq!(
+            parse_url("gitlab:user/repo", None).unwrap(),
+            "https://gitlab.com/user/repo"
+        );
+        assert_eq!(
+            parse_url("bitbucket:user/repo", None).unwrap(),
+            "https://bitbucket.org/user/repo"
+        );
+        assert_eq!(
+            parse_url("gist:11081aaa281", None).unwrap(),
+            "https://gist.github.com/11081aaa281"
+        );
+        assert_eq!(
+            parse_url("git@gitlab.com:group/sub/repo.git", None).unwrap(),
+            "https://gitlab.com/group/sub/repo"
+        );
+        assert_eq!(
+            parse_url("http://git.example.com:8080/user/repo.git", None).unwrap(),
+            "http://git.example.com:8080/user/repo"
+        );
+    }
+
+    #[test]
+    fn test_parse_url_with_directory() {
+        assert_eq!(
+            parse_url(
+                "https://github.com/facebook/react.git",
+                Some("packages/react")
+            )
+            .unwrap(),
+            "https://github.com/facebook/react/tree/HEAD/packages/react"
+        );
+        assert_eq!(
+            parse_url("gitlab:user/repo", Some("/packages/core/")).unwrap(),
+            "https://gitlab.com/user/repo/-/tree/HEAD/packages/core"
+        );
+        assert_eq!(
+            parse_url("bitbucket:user/repo", Some("packages/core")).unwrap(),
+            "https://bitbucket.org/user/repo/src/HEAD/packages/core"
+        );
+        assert_eq!(
+            parse_url("github:user/repo", Some("")).unwrap(),
+            "https://github.com/user/repo"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_url() {
+        for url in [
+            "",
+            "  ",
+            "repo",
+            "npm:repo",
+            "https://github.com",
+            "git@github.com:",
+            "user/repo with space",
+        ] {
+            assert!(parse_url(url, None).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_set_package_manager() {
+        assert_eq!(
+            set_package_manager(
+                "{\n  \"name\": \"ri\",\n  \"packageManager\": \"yarn@1.22.19\"\n}\n",
+                "pnpm@8.6.0"
+            )
+            .unwrap(),
+            "{\n  \"name\": \"ri\",\n  \"packageManager\": \"pnpm@8.6.0\"\n}\n"
+        );
+        assert_eq!(
+            set_package_manager("{\n    \"name\": \"ri\"\n}\n", "pnpm@8.6.0").unwrap(),
+            "{\n    \"name\": \"ri\",\n    \"packageManager\": \"pnpm@8.6.0\"\n}\n"
+        );
+        assert_eq!(
+            set_package_manager("{}", "bun@1.0.0").unwrap(),
+            "{\n  \"packageManager\": \"bun@1.0.0\"\n}"
+        );
+        assert_eq!(
+            set_package_manager("{\r\n  \"name\": \"x\"\r\n}\r\n", "npm@10.0.0").unwrap(),
+            "{\r\n  \"name\": \"x\",\r\n  \"packageManager\": \"npm@10.0.0\"\r\n}\r\n"
+        );
+        assert_eq!(set_package_manager("", "bun@1.0.0"), None);
+        assert_eq!(
+            set_package_manager("{\"name\": \"ri\",}", "bun@1.0.0"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_package_manager_nested() {
+        let contents = "{\n  \"name\": \"ri\",\n  \"devEngines\": {\n    \"packageManager\": {\"name\": \"pnpm\", \"onFail\": \"warn\"}\n  }\n}\n";
+        let result = set_package_manager(contents, "pnpm@8.6.0").unwrap();
+        assert_eq!(
+            result,
+            "{\n  \"name\": \"ri\",\n  \"devEngines\": {\n    \"packageManager\": {\"name\": \"pnpm\", \"onFail\": \"warn\"}\n  },\n  \"packageManager\": \"pnpm@8.6.0\"\n}\n"
+        );
+        let value = serde_json::from_str::<serde_json::Value>(&result).unwrap();
+        assert_eq!(value["packageManager"], "pnpm@8.6.0");
+        assert_eq!(value["devEngines"]["packageManager"]["name"], "pnpm");
+
+        // the key in a string value isn't a field
+        let contents =
+            "{\"description\": \"\\\"packageManager\\\": x\", \"packageManager\": \"npm@9.0.0\"}";
+        assert_eq!(
+            set_package_manager(contents, "bun@1.0.0").unwrap(),
+            "{\"description\": \"\\\"packageManager\\\": x\", \"packageManager\": \"bun@1.0.0\"}"
+        );
     }
 }
@@ -1,14 +1,51 @@
 use crate::{
-    agents::{self, Agent},
-    cargo_toml::CargoToml,
+    agents::{self, Agent, Agents},
+    clean,
     commands::Command,
+    config::{Config, InstallMode},
     error::CommonError,
+    git, migrate,
+    opener::Opener,
     opt::{Opt, SubCommand},
-    package_json::PackageJson,
+    package_json::{self, PackageJson},
+    paste,
+    repo::{self, RepoTarget},
+    runner::Runner,
+    scripts::{self, Resolved},
     utils::{self, exclude, is_a_git_clone_url},
+    workspace,
 };
-use clipboard::{ClipboardContext, ClipboardProvider};
-use std::fs;
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// types of conventional commits, see https://www.conventionalcommits.org
+const COMMIT_TYPES: [(&str, &str); 11] = [
+    ("feat", "A new feature"),
+    ("fix", "A bug fix"),
+    ("docs", "Documentation only changes"),
+    (
+        "style",
+        "Changes that do not affect the meaning of the code",
+    ),
+    (
+        "refactor",
+        "A code change that neither fixes a bug nor adds a feature",
+    ),
+    ("perf", "A code change that improves performance"),
+    ("test", "Adding missing tests or correcting existing tests"),
+    (
+        "build",
+        "Changes that affect the build system or external dependencies",
+    ),
+    ("ci", "Changes to CI configuration files and scripts"),
+    ("chore", "Other changes that don't modify src or test files"),
+    ("revert", "Reverts a previous commit"),
+];
+
+const NO_SCOPE: &str = "(none)";
 
 #[derive(Debug)]
 pub struct Parser {
@@ -47,32 +84,140 @@ impl Parser {
                         args: Some(package_name.clone()),
                     }),
                 },
-                SubCommand::Rm => Ok(Parser {
-                    command: Command::RemoveNodeModules,
+                SubCommand::Rm { store: true, .. } => Ok(Parser {
+                    command: Command::CleanCache,
                     args: None,
                 }),
-                SubCommand::Rl => Ok(Parser {
+                SubCommand::Rm {
+                    build, cache, all, ..
+                } if *build || *cache || *all => {
+                    let config = Config::load()?.clean;
+                    let mut names = vec![];
+                    if *all {
+                        names.push("node_modules".to_string());
+                    }
+                    if *build || *all {
+                        names.extend(config.build.unwrap_or(to_strings(&clean::BUILD_DIRS)));
+                    }
+                    if *cache || *all {
+                        names.extend(config.cache.unwrap_or(to_strings(&clean::CACHE_DIRS)));
+                    }
+
+                    let names = names
+                        .iter()
+                        .map(|name| name.as_str())
+                        .collect::<Vec<&str>>();
+                    let targets = clean::find_dirs(Path::new("."), &names);
+                    Ok(Parser {
+                        command: Command::RemoveBuildFiles,
+                        args: Some(targets.iter().map(|path| display_path(path)).collect()),
+                    })
+                }
+                SubCommand::Rm { recursive, .. } => {
+                    let targets = match recursive {
+                        true => clean::find_dirs(Path::new("."), &["node_modules"]),
+                        // a symlinked node_modules isn't ours to remove
+                        false => match fs::symlink_metadata("node_modules") {
+                            Ok(metadata) if metadata.is_dir() => {
+                                vec![PathBuf::from("node_modules")]
+                            }
+                            _ => vec![],
+                        },
+                    };
+                    Ok(Parser {
+                        command: Command::RemoveNodeModules,
+                        args: Some(targets.iter().map(|path| display_path(path)).collect()),
+                    })
+                }
+                SubCommand::Rl { .. } => Ok(Parser {
                     command: Command::RemoveLockFile,
-                    args: None,
+                    args: Some(Agents::new().existing_lock_files()),
                 }),
-                SubCommand::Info => Ok(Parser {
-                    command: Command::PkgInfo,
-                    args: None,
+                SubCommand::Use { agent } => Ok(Parser {
+                    command: Command::UseAgent,
+                    args: Some(vec![agent.to_lowercase()]),
+                }),
+                SubCommand::Migrate { agent } => Ok(Parser {
+                    command: Command::Migrate,
+                    args: Some(vec![agent.to_lowercase()]),
                 }),
-                SubCommand::Repo => Ok(Parser {
-                    command: Command::PkgRepo,
+                SubCommand::Info {
+                    package_name,
+                    fields,
+                } => match package_name {
+                    None => Ok(Parser {
+                        command: Command::PkgInfo,
+                        args: None,
+                    }),
+                    Some(name) if utils::is_cargo_project() => match fields.is_empty() {
+                        true => Ok(Parser {
+                            command: Command::CrateInfo,
+                            args: Some(vec![name.to_string()]),
+                        }),
+                        false => Err(CommonError::Unsupported(
+                            "cargo info doesn't support field selection".to_string(),
+                        )),
+                    },
+                    Some(name) => {
+                        let mut args = vec![name.to_string()];
+                        args.extend(fields.iter().cloned());
+                        Ok(Parser {
+                            command: Command::PkgView,
+                            args: Some(args),
+                        })
+                    }
+                },
+                SubCommand::Repo { name, target, .. } => {
+                    // `ri repo issues` opens a page of current package, `ri repo react` opens a dependency
+                    let (dependency, target) = match (name, target) {
+                        (Some(name), None) => match name.parse::<RepoTarget>() {
+                            Ok(target) => (None, Some(target)),
+                            Err(_) => (Some(name), None),
+                        },
+                        (name, target) => (name.as_ref(), *target),
+                    };
+                    let dir = match dependency {
+                        Some(name) => repo::find_dependency_dir(name)?,
+                        None => PathBuf::from("."),
+                    };
+                    let url = match target {
+                        Some(target) => repo::get_target_url(&dir, target)?,
+                        None => repo::get_repo_url(&dir, dependency.is_some())?,
+                    };
+                    Ok(Parser {
+                        command: Command::PkgRepo,
+                        args: Some(vec![url]),
+                    })
+                }
+                SubCommand::R { list: true, .. } => Ok(Parser {
+                    command: Command::ListScripts,
                     args: None,
                 }),
-                SubCommand::R { run_name } => match run_name {
+                SubCommand::R {
+                    parallel,
+                    run_name,
+                    args,
+                    ..
+                } => match run_name.as_deref() {
                     None => {
                         let package_json = PackageJson::from_path("package.json")?;
-                        let script = package_json.scripts.ok_or(CommonError::NotFound(
+                        let script = package_json.scripts.clone().ok_or(CommonError::NotFound(
                             "package.json scripts field not found!".to_string(),
                         ))?;
 
-                        let script_choices = script
+                        let history = scripts::read_history(Path::new(scripts::HISTORY_PATH));
+                        let names =
+                            scripts::sort_scripts(script.keys().cloned().collect(), &history);
+                        let script_choices = names
                             .iter()
-                            .map(|(k, v)| format!("{} - {}", k, v))
+                            .map(|name| {
+                                let description = package_json.get_script_description(name);
+                                format!(
+                                    "{} - {}",
+                                    name,
+                                    description.unwrap_or(script[name].clone())
+                                )
+                            })
                             .collect::<Vec<String>>();
 
                         match script_choices.len() {
@@ -80,7 +225,7 @@ impl Parser {
                                 "package.json scripts field is empty!".to_string(),
                             )),
                             _ => {
-                                let ans = utils::select_a_choice(
+                                let ans = utils::select_a_fuzzy_choice(
                                     &script_choices,
                                     "run",
                                     "Script to run",
@@ -93,46 +238,168 @@ impl Parser {
                             }
                         }
                     }
-                    Some(name) => Ok(Parser {
-                        command: Command::Run,
-                        args: Some(vec![name.to_string()]),
-                    }),
+                    Some("-") => rerun(args),
+                    Some(name) => parse_run(*parallel, name, args),
                 },
-                SubCommand::Cl { src } => match src {
-                    Some(src) => Ok(Parser {
+                SubCommand::Cl {
+                    src, dir, depth, ..
+                } => {
+                    let src = match src {
+                        Some(src) => Some(src.to_string()),
+                        None => match paste::read_clipboard() {
+                            Ok(content) if is_a_git_clone_url(&content) => Some(content),
+                            _ => Some(utils::ask_input_question(
+                                "No repository url in the clipboard, input one",
+                                |url| match repo::to_clone_url(url) {
+                                    Some(_) => Ok(()),
+                                    None => Err(format!("`{}` is not a repository url", url)),
+                                },
+                            )?),
+                        },
+                    };
+
+                    let mut args = vec![];
+                    if let Some(src) = src {
+                        let url = repo::to_clone_url(&src).ok_or(CommonError::NotFound(
+                            format!("`{}` is not a repository url", src),
+                        ))?;
+                        let dir = match dir {
+                            Some(dir) => dir.to_string(),
+                            None => repo::get_repo_name(&url).unwrap_or_default(),
+                        };
+                        if let Some(depth) = depth {
+                            args.push("--depth".to_string());
+                            args.push(depth.to_string());
+                        }
+                        args.push(url);
+                        // the directory is always the last arg
+                        args.push(dir);
+                    }
+                    Ok(Parser {
                         command: Command::GitClone,
-                        args: Some(vec![src.to_string()]),
+                        args: Some(args),
+                    })
+                }
+                SubCommand::Pl {
+                    rebase,
+                    no_rebase,
+                    args,
+                    ..
+                } => {
+                    let rebase = match (rebase, no_rebase) {
+                        (true, _) => Some(true),
+                        (_, true) => Some(false),
+                        _ => Config::load()?.git.pull_rebase,
+                    };
+                    let mut git_args = match rebase {
+                        Some(true) => vec!["--rebase".to_string()],
+                        Some(false) => vec!["--no-rebase".to_string()],
+                        None => vec![],
+                    };
+                    git_args.extend(args.iter().map(|arg| utils::quote(arg)));
+                    Ok(Parser {
+                        command: Command::GitPull,
+                        args: Some(git_args),
+                    })
+                }
+                SubCommand::Ps { args } => Ok(Parser {
+                    command: Command::GitPush,
+                    args: Some(args.iter().map(|arg| utils::quote(arg)).collect()),
+                }),
+                SubCommand::Log { max_count, args } => {
+                    let mut git_args = match max_count {
+                        Some(n) => vec![format!("-n {}", n)],
+                        None => vec![],
+                    };
+                    git_args.extend(args.iter().map(|arg| utils::quote(arg)));
+                    Ok(Parser {
+                        command: Command::GitLog,
+                        args: Some(git_args),
+                    })
+                }
+                SubCommand::Co { create, branch } => match (create, branch) {
+                    (true, Some(branch)) => Ok(Parser {
+                        command: Command::GitCheckout,
+                        args: Some(vec!["-b".to_string(), utils::quote(branch)]),
                     }),
-                    None => {
-                        // TODO: catch error
-                        let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-                        let content = ctx.get_contents().unwrap();
-                        let mut args = vec![];
-                        if is_a_git_clone_url(&content) {
-                            args.push(content);
+                    (true, None) => Err(CommonError::NotFound(
+                        "the name of new branch not found".to_string(),
+                    )),
+                    (false, Some(branch)) => Ok(Parser {
+                        command: Command::GitCheckout,
+                        args: Some(vec![utils::quote(branch)]),
+                    }),
+                    (false, None) => {
+                        let branches = git::recent_branches();
+                        if branches.is_empty() {
+                            return Err(CommonError::NotFound(
+                                "no other branch to checkout".to_string(),
+                            ));
                         }
+                        let branch =
+                            utils::select_a_choice(&branches, "branch", "Branch to checkout")?;
                         Ok(Parser {
-                            command: Command::GitClone,
-                            args: Some(args),
+                            command: Command::GitCheckout,
+                            args: Some(vec![utils::quote(&branch)]),
                         })
                     }
                 },
-                SubCommand::Pl => Ok(Parser {
-                    command: Command::GitPull,
-                    args: None,
-                }),
-                SubCommand::Ps => Ok(Parser {
-                    command: Command::GitPush,
-                    args: None,
-                }),
-                SubCommand::Log => Ok(Parser {
-                    command: Command::GitLog,
+                SubCommand::Br { delete } => Ok(Parser {
+                    command: match delete {
+                        true => Command::GitBranchDelete,
+                        false => Command::GitBranch,
+                    },
                     args: None,
                 }),
+                SubCommand::Cm { .. } => {
+                    let types = COMMIT_TYPES
+                        .iter()
+                        .map(|(t, description)| format!("{} - {}", t, description))
+                        .collect::<Vec<String>>();
+                    let commit_type = utils::select_a_choice(&types, "type", "Type of change")?;
+
+                    let packages = workspace::get_packages(Path::new("."))
+                        .into_iter()
+                        .filter_map(|pkg| pkg.name)
+                        .collect::<Vec<String>>();
+                    let scope = match packages.is_empty() {
+                        true => utils::ask_input_question("Scope (optional)", |_| Ok(()))?,
+                        false => {
+                            let mut scopes = vec![NO_SCOPE.to_string()];
+                            // `@scope/core` is too long for a commit message
+                            scopes.extend(
+                                packages
+                                    .iter()
+                                    .map(|name| name.rsplit('/').next().unwrap().to_string()),
+                            );
+                            match utils::select_a_choice(&scopes, "scope", "Scope")? {
+                                scope if scope == NO_SCOPE => "".to_string(),
+                                scope => scope,
+                            }
+                        }
+                    };
+
+                    let subject = utils::ask_input_question("Commit message", |message| {
+                        match message.trim().is_empty() {
+                            true => Err("commit message is required".to_string()),
+                            false => Ok(()),
+                        }
+                    })?;
+
+                    let message = match scope.is_empty() {
+                        true => format!("{}: {}", commit_type, subject),
+                        false => format!("{}({}): {}", commit_type, scope, subject),
+                    };
+                    Ok(Parser {
+                        command: Command::GitCommit,
+                        args: Some(vec!["-m".to_string(), utils::quote(&message)]),
+                    })
+                }
                 SubCommand::Rd => Ok(Parser {
                     command: Command::Run,
                     args: Some(vec!["dev".to_string()]),
                 }),
+                SubCommand::Rr => rerun(&[]),
                 SubCommand::Other(v) => Ok(Parser::parser_other_args(v.clone())),
             },
         }
@@ -153,62 +420,399 @@ impl Parser {
 }
 
 impl Parser {
+    /// remove the directories in args after confirm, or list them in dry run
+    fn remove_targets(&self, label: &str, opt: &Opt) -> Result<String, CommonError> {
+        let targets = self.args.as_ref().unwrap();
+        if targets.is_empty() {
+            println!("{} not found, nothing to remove", label);
+            return Ok("".to_string());
+        }
+
+        let targets = targets.iter().map(PathBuf::from).collect::<Vec<PathBuf>>();
+        let sizes = clean::dirs_sizes(&targets);
+        let size = clean::format_size(sizes.iter().sum());
+
+        let is_dry_run = matches!(opt.cmd, Some(SubCommand::Rm { dry_run: true, .. }));
+        if targets.len() > 1 || is_dry_run {
+            for (path, size) in targets.iter().zip(&sizes) {
+                println!("  {:<50} {:>10}", path.display(), clean::format_size(*size));
+            }
+        }
+        if is_dry_run {
+            let unit = match targets.len() {
+                1 => "directory",
+                _ => "directories",
+            };
+            println!("{} {}, {} in total", targets.len(), unit, size);
+            return Ok("".to_string());
+        }
+
+        let question = match targets.len() {
+            1 => format!("Do you want to remove {} ({})?", targets[0].display(), size),
+            n => format!(
+                "Do you want to remove {} directories of {} ({})?",
+                n, label, size
+            ),
+        };
+        let is_remove = utils::ask_confirm_question(&question)?;
+
+        if is_remove & !opt.debug {
+            clean::remove_dirs(&targets)?;
+            println!("{} removed success!", label)
+        }
+        Ok("".to_string())
+    }
+
     pub fn gene_command(&mut self, opt: &Opt) -> Result<String, CommonError> {
         match self.command {
-            Command::IgnoredCommand => Ok("".to_string()),
+            Command::Ignored => Ok("".to_string()),
             Command::GitClone => {
-                let src = self.args.as_ref().unwrap();
-                if src.len() == 0 {
+                let args = self.args.as_ref().unwrap();
+                if args.is_empty() {
                     return Err(CommonError::NotFound(
                         ("repository url not found").to_string(),
                     ));
                 }
-                let src = &src[0];
-                Ok(format!("git clone {}", src))
+                // the args are kept raw, the directory is used to enter after clone
+                let quoted = args
+                    .iter()
+                    .map(|arg| utils::quote(arg))
+                    .collect::<Vec<String>>();
+                let clone = format!("git clone {}", quoted.join(" "));
+
+                let is_install = matches!(opt.cmd, Some(SubCommand::Cl { install: true, .. }));
+                if !is_install || opt.debug {
+                    return Ok(clone);
+                }
+
+                // clone first, then detect the agent of the cloned repository to install
+                println!("Execute: {}", clone);
+                Runner::run(&clone)?;
+                env::set_current_dir(args.last().unwrap())?;
+
+                self.command = Command::Install;
+                self.args = None;
+                self.gene_command(opt)
+            }
+            Command::GitCheckout => {
+                let args = self.args.as_ref().unwrap();
+                Ok(format!("git checkout {}", args.join(" ")))
+            }
+            Command::GitCommit => {
+                let args = self.args.as_ref().unwrap();
+                let commit = format!("git commit {}", args.join(" "));
+                match opt.cmd {
+                    Some(SubCommand::Cm { all: true }) => Ok(format!("git add -A && {}", commit)),
+                    _ => Ok(commit),
+                }
+            }
+            Command::GitBranch => Ok("git branch".to_string()),
+            Command::GitBranchDelete => {
+                let branches = git::merged_branches();
+                if branches.is_empty() {
+                    println!("No merged branch to delete");
+                    return Ok("".to_string());
+                }
+
+                let is_delete = utils::ask_confirm_question(&format!(
+                    "Do you want to delete merged branches: {}?",
+                    branches.join(", ")
+                ))?;
+
+                match is_delete {
+                    true => Ok(format!("git branch -d {}", branches.join(" "))),
+                    false => Ok("".to_string()),
+                }
             }
-            Command::GitPull => Ok("git pull".to_string()),
-            Command::GitPush => Ok("git push".to_string()),
-            Command::GitLog => Ok("git log --graph --oneline --decorate".to_string()),
-            Command::RemoveNodeModules => {
-                let is_remove = utils::ask_confirm_question("Do you want to remove node_modules?")?;
+            Command::CrateInfo => {
+                let name = self.args.as_ref().unwrap();
+                Ok(format!("cargo info {}", name.join(" ")))
+            }
+            Command::GitPull => {
+                let args = self.args.as_ref().unwrap();
+                let pull = format!("git pull {}", args.join(" ")).trim().to_string();
 
-                if is_remove & !opt.debug {
-                    utils::remove_dir_all_file_with_path("node_modules")?;
-                    println!("node_modules removed success!")
+                let install_mode = match opt.cmd {
+                    Some(SubCommand::Pl { install: true, .. }) => InstallMode::Auto,
+                    Some(SubCommand::Pl {
+                        no_install: true, ..
+                    }) => InstallMode::Never,
+                    _ => Config::load()?.git.pull_install.unwrap_or_default(),
+                };
+                let is_node_project = Path::new("package.json").exists();
+                if opt.debug || install_mode == InstallMode::Never || !is_node_project {
+                    return Ok(pull);
                 }
-                Ok("".to_string())
+
+                let lock_files = Agents::new().lock_map.into_keys().collect::<Vec<String>>();
+                let lock_hash = utils::hash_files(&lock_files);
+                let package_hash = utils::hash_files(&["package.json"]);
+
+                println!("Execute: {}", pull);
+                Runner::run(&pull)?;
+
+                let is_lock_changed = lock_hash != utils::hash_files(&lock_files);
+                let is_package_changed = package_hash != utils::hash_files(&["package.json"]);
+                if !is_lock_changed && !is_package_changed {
+                    return Ok("".to_string());
+                }
+
+                if install_mode == InstallMode::Prompt
+                    && !utils::ask_confirm_question(
+                        "Dependencies changed, do you want to install them?",
+                    )?
+                {
+                    return Ok("".to_string());
+                }
+
+                // the lockfile is the truth if it's changed, otherwise update it by installing
+                self.command = match is_lock_changed {
+                    true => Command::Frozen,
+                    false => Command::Install,
+                };
+                self.args = None;
+                self.gene_command(opt)
+            }
+            Command::GitPush => {
+                let args = self.args.as_ref().unwrap();
+                // the first push of a new branch, unless the remote or refspec is given
+                let has_refspec = args.iter().any(|arg| !arg.starts_with('-'));
+                if !has_refspec && !git::has_upstream() {
+                    if let (Some(remote), Some(branch)) =
+                        (git::push_remote(), git::current_branch())
+                    {
+                        let mut args = args.clone();
+                        args.extend(["-u".to_string(), remote, branch]);
+                        return Ok(format!("git push {}", args.join(" ")));
+                    }
+                }
+                Ok(format!("git push {}", args.join(" ")).trim().to_string())
+            }
+            Command::GitLog => {
+                let args = self.args.as_ref().unwrap();
+                Ok(
+                    format!("git log --graph --oneline --decorate {}", args.join(" "))
+                        .trim()
+                        .to_string(),
+                )
             }
+            Command::RemoveNodeModules => self.remove_targets("node_modules", opt),
+            Command::RemoveBuildFiles => self.remove_targets("build outputs and caches", opt),
             Command::RemoveLockFile => {
-                let is_remove = utils::ask_confirm_question("Do you want to remove lockfile?")?;
+                let mut lock_files = self.args.clone().unwrap();
+                if lock_files.is_empty() {
+                    println!("lockfile not found, nothing to remove");
+                    return Ok("".to_string());
+                }
+
+                // `--all` removes every lockfile, the agent is only needed to reinstall
+                let mut agent = None;
+                if !matches!(opt.cmd, Some(SubCommand::Rl { all: true, .. })) {
+                    // without package.json, the lockfiles are all that tell the agent
+                    let current = match agents::get_lock_file_agent(&lock_files) {
+                        Some(agent) if !Path::new("package.json").is_file() => agent,
+                        _ => agents::get_current_agent(opt.debug)?,
+                    };
+                    let lock_map = Agents::new().lock_map;
+                    lock_files.retain(|file_name| lock_map.get(file_name) == Some(&current));
+                    if lock_files.is_empty() {
+                        println!(
+                            "lockfile of {} not found, use `ri rl --all` to remove the others",
+                            String::from(current)
+                        );
+                        return Ok("".to_string());
+                    }
+                    agent = Some(current);
+                }
+
+                let question = format!("Do you want to remove {}?", lock_files.join(", "));
+                if !utils::ask_confirm_question(&question)? {
+                    return Ok("".to_string());
+                }
+                let is_install = match opt.cmd {
+                    // nothing to install without package.json
+                    _ if !Path::new("package.json").is_file() => false,
+                    Some(SubCommand::Rl { install: true, .. }) => true,
+                    _ => utils::ask_confirm_question(
+                        "Do you want to install to regenerate the lockfile?",
+                    )?,
+                };
+                // detect the agent before the lockfiles telling it are gone
+                let agent = match (agent, is_install) {
+                    (Some(agent), true) => Some(agent),
+                    (None, true) => Some(agents::get_current_agent(opt.debug)?),
+                    (_, false) => None,
+                };
 
-                if is_remove & !opt.debug {
-                    utils::remove_lock_files()?;
+                if !opt.debug {
+                    utils::remove_lock_files(&lock_files)?;
                     println!("lockfile removed success!")
                 }
+
+                let install = agent
+                    .and_then(|agent| Agent::get_agent_hash_map(agent).remove(&Command::Install))
+                    .flatten();
+                match install {
+                    Some(cmd) => Ok(cmd.replace("$0", "").trim().to_string()),
+                    None => Ok("".to_string()),
+                }
+            }
+            Command::UseAgent => {
+                let arg = &self.args.as_ref().unwrap()[0];
+                let (name, version) = match arg.split_once('@') {
+                    Some((name, version)) => (name, Some(version.to_string())),
+                    None => (arg.as_str(), None),
+                };
+                let agent = to_agent(name)?;
+                let version = match version {
+                    Some(version) if !version.is_empty() => version,
+                    _ => agents::get_version(agent).ok_or_else(|| {
+                        CommonError::NotFound(format!(
+                            "{} is not installed, specify the version like `{}@1.0.0`",
+                            name, name
+                        ))
+                    })?,
+                };
+
+                let change = package_json::update_package_manager(
+                    &format!("{}@{}", name, version),
+                    opt.debug,
+                )?;
+                println!("{}", change);
                 Ok("".to_string())
             }
-            Command::PkgRepo => match fs::read_dir(".") {
-                Ok(rd) => {
-                    let files: Vec<_> = rd
-                        .filter(|d| d.is_ok())
-                        .map(|d| d.unwrap().file_name().into_string().unwrap())
-                        .collect();
-
-                    let url = if files.contains(&"package.json".to_string()) {
-                        PackageJson::from_path("package.json")?.get_url()?
-                    } else if files.contains(&"Cargo.toml".to_string()) {
-                        CargoToml::from_path("Cargo.toml")?.get_url()?
-                    } else {
-                        return Err(CommonError::NotFound(
-                            "package.json or Cargo.toml not found!".to_string(),
-                        ));
-                    };
-                    Ok(format!("start {}", url))
+            Command::CleanCache => {
+                let agent = agents::get_current_agent(opt.debug)?;
+                let clean = match Agent::get_agent_hash_map(agent).get(&Command::CleanCache) {
+                    Some(Some(cmd)) => cmd.to_string(),
+                    _ => return Ok("".to_string()),
+                };
+                let question = format!("Do you want to clear the global cache by `{}`?", clean);
+                match utils::ask_confirm_question(&question)? {
+                    true => Ok(clean),
+                    false => Ok("".to_string()),
                 }
-                Err(_) => Err(CommonError::NotFound(
-                    "package.json or Cargo.toml not found!".to_string(),
-                )),
-            },
+            }
+            Command::ListScripts => {
+                let mut packages = vec![PackageJson::from_path("package.json")?];
+                packages.extend(workspace::get_packages(Path::new(".")));
+
+                if matches!(opt.cmd, Some(SubCommand::R { json: true, .. })) {
+                    let list = packages
+                        .iter()
+                        .map(|package| {
+                            serde_json::json!({
+                                "package": package.name,
+                                "scripts": scripts::get_script_infos(package),
+                            })
+                        })
+                        .collect::<Vec<serde_json::Value>>();
+                    println!("{}", serde_json::to_string_pretty(&list)?);
+                    return Ok("".to_string());
+                }
+
+                for (index, package) in packages.iter().enumerate() {
+                    let infos = scripts::get_script_infos(package);
+                    if index > 0 && infos.is_empty() {
+                        continue;
+                    }
+                    if packages.len() > 1 {
+                        let name = package.name.as_deref().unwrap_or("(no name)");
+                        println!("{}{}", if index > 0 { "\n" } else { "" }, name);
+                    }
+
+                    let width = infos.iter().map(|info| info.name.len()).max().unwrap_or(0);
+                    for info in infos {
+                        match info.description {
+                            Some(description) => println!(
+                                "  {:<width$}  {}  # {}",
+                                info.name,
+                                info.command,
+                                description,
+                                width = width
+                            ),
+                            None => {
+                                println!("  {:<width$}  {}", info.name, info.command, width = width)
+                            }
+                        }
+                    }
+                }
+                Ok("".to_string())
+            }
+            Command::RunScripts | Command::RunScriptsParallel => {
+                let names = self.args.as_ref().unwrap();
+                let is_parallel = self.command == Command::RunScriptsParallel;
+                let run = match is_parallel {
+                    true => [vec!["-p".to_string()], names.clone()].concat(),
+                    false => names.clone(),
+                };
+                record_run(&run, opt.debug);
+                let agent = agents::get_current_agent(opt.debug)?;
+                let run = Agent::get_agent_hash_map(agent)[&Command::Run]
+                    .clone()
+                    .unwrap_or_default();
+                let commands = names
+                    .iter()
+                    .map(|name| (name.to_string(), run.replace("$0", &utils::quote(name))))
+                    .collect::<Vec<(String, String)>>();
+
+                if is_parallel {
+                    for (_, command) in &commands {
+                        println!("Execute: {}", command);
+                    }
+                    if !opt.debug {
+                        Runner::run_parallel(&commands)?;
+                    }
+                    return Ok("".to_string());
+                }
+
+                // stop at the first failed script
+                for (_, command) in &commands {
+                    println!("Execute: {}", command);
+                    if !opt.debug {
+                        Runner::run(command)?;
+                    }
+                }
+                Ok("".to_string())
+            }
+            Command::Migrate => {
+                let name = &self.args.as_ref().unwrap()[0];
+                let target = to_agent(name)?;
+                let agent = agents::get_current_agent(opt.debug)?;
+                if agent == target {
+                    println!("{} is used already, nothing to migrate", name);
+                    return Ok("".to_string());
+                }
+                let version = agents::get_version(target)
+                    .ok_or_else(|| CommonError::NotFound(format!("{} is not installed", name)))?;
+                let changes = migrate::migrate(agent, target, &version, opt.debug)?;
+
+                println!(
+                    "Migrated from {} to {}:",
+                    String::from(agent),
+                    String::from(target)
+                );
+                for change in changes {
+                    println!("  - {}", change);
+                }
+                Ok("".to_string())
+            }
+            Command::PkgRepo => {
+                let url = &self.args.as_ref().unwrap()[0];
+
+                if let Some(SubCommand::Repo { print: true, .. }) = opt.cmd {
+                    println!("{}", url);
+                    return Ok("".to_string());
+                }
+
+                let opener = Opener::from_env();
+                println!("Open: {} with {}", url, opener);
+
+                if !opt.debug {
+                    opener.open(url)?;
+                }
+                Ok("".to_string())
+            }
             Command::PkgInfo => {
                 let package_json = PackageJson::from_path("package.json")?;
 
@@ -225,39 +829,186 @@ impl Parser {
                 Ok("".to_string())
             }
             _ => {
-                let agent = agents::get_current_agent()?;
+                let agent = agents::get_current_agent(opt.debug)?;
 
                 let hash_map = Agent::get_agent_hash_map(agent);
 
                 // instand of yarn install xxx => yarn add xxx
-                match &agent {
-                    Agent::Yarn | Agent::Pnpm => {
-                        if self.command == Command::Install && self.args.is_some() {
-                            self.command = Command::Add
-                        }
+                if let Agent::Yarn | Agent::Pnpm = &agent {
+                    if self.command == Command::Install && self.args.is_some() {
+                        self.command = Command::Add
                     }
-                    _ => (),
                 };
 
+                if self.command == Command::ExecuteLocal {
+                    let run = self.args.clone().unwrap_or_default();
+                    record_run(&run, opt.debug);
+                    self.args = Some(run.iter().map(|arg| utils::quote(arg)).collect());
+                }
+
+                if self.command == Command::Run {
+                    let run = self.args.clone().unwrap_or_default();
+                    record_run(&run, opt.debug);
+
+                    let mut args = run.iter().take(1).cloned().collect::<Vec<String>>();
+                    // npm takes the args after `--` as the ones of the script
+                    if matches!(agent, Agent::Npm | Agent::None) && run.len() > 1 {
+                        args.push("--".to_string());
+                    }
+                    args.extend(run.iter().skip(1).map(|arg| utils::quote(arg)));
+                    self.args = Some(args);
+                }
+
                 match hash_map.get(&self.command) {
-                    Some(cmd) => match &cmd {
-                        Some(cmd) => {
-                            let command = cmd.clone();
-                            if command.contains("$0") {
-                                match &self.args {
-                                    None => Ok(command.replace("$0", "").trim().to_string()),
-                                    Some(arg) => Ok(command.replace("$0", &arg.join(" "))),
-                                }
-                            } else {
-                                Ok(command)
+                    Some(Some(cmd)) => {
+                        let command = cmd.clone();
+                        if command.contains("$0") {
+                            match &self.args {
+                                None => Ok(command.replace("$0", "").trim().to_string()),
+                                Some(arg) => Ok(command.replace("$0", &arg.join(" "))),
                             }
+                        } else {
+                            Ok(command)
                         }
-                        None => Ok("".to_string()),
-                    },
-                    None => Ok("".to_string()),
+                    }
+                    _ => Ok("".to_string()),
                 }
             }
         }
         // don't need get agent or execute command
     }
 }
+
+/// `ri r <name> [...args]`, the args are the scripts to run with `-p` or if they all are,
+/// otherwise the ones of the script
+fn parse_run(parallel: bool, name: &str, args: &[String]) -> Result<Parser, CommonError> {
+    let names = [vec![name.to_string()], args.to_vec()].concat();
+    let scripts = PackageJson::from_path("package.json")
+        .ok()
+        .and_then(|package_json| package_json.scripts)
+        .unwrap_or_default();
+    // `ri r lint test` runs both scripts, `ri r test --watch` passes the args
+    let is_scripts = names.len() > 1 && names.iter().all(|name| scripts.contains_key(name));
+    if !parallel && !is_scripts {
+        return resolve_script(name, args, &scripts);
+    }
+
+    if let Some(name) = names.iter().find(|name| !scripts.contains_key(*name)) {
+        return Err(CommonError::NotFound(format!(
+            "script `{}` not found in package.json",
+            name
+        )));
+    }
+    Ok(Parser {
+        command: match parallel {
+            true => Command::RunScriptsParallel,
+            false => Command::RunScripts,
+        },
+        args: Some(names),
+    })
+}
+
+/// run the last run of `ri r` again, with more args
+fn rerun(args: &[String]) -> Result<Parser, CommonError> {
+    let mut run = last_run()?;
+    let parallel = run.first().map(String::as_str) == Some("-p");
+    if parallel {
+        run.remove(0);
+    }
+    run.extend(args.iter().cloned());
+    match run.split_first() {
+        Some((name, args)) => parse_run(parallel, name, args),
+        None => Err(CommonError::NotFound(
+            "no script has been run by ri yet".to_string(),
+        )),
+    }
+}
+
+/// record a run of `ri r` for `ri rr`, like `test --watch`, `lint test`, `-p dev:api dev:web`
+/// or `eslint --fix` of a binary
+fn record_run(run: &[String], debug: bool) {
+    if !debug {
+        // the history only helps to pick and rerun scripts, it's fine to lose it
+        let _ = scripts::record_history(Path::new(scripts::HISTORY_PATH), run);
+    }
+}
+
+/// the script meant by the name, then the binary of dependencies with the name, then a
+/// unique prefix of scripts, and the similar scripts are offered at last
+fn resolve_script(
+    name: &str,
+    args: &[String],
+    scripts: &HashMap<String, String>,
+) -> Result<Parser, CommonError> {
+    let run = |name: String| Parser {
+        command: Command::Run,
+        args: Some([vec![name], args.to_vec()].concat()),
+    };
+    let names = scripts.keys().cloned().collect::<Vec<String>>();
+    let candidates = match scripts::resolve_run(name, &names, &env::current_dir()?) {
+        Resolved::Script(resolved) => {
+            if resolved != name {
+                println!("`{}` is resolved to script `{}`", name, resolved);
+            }
+            return Ok(run(resolved));
+        }
+        Resolved::Bin(_) => {
+            println!(
+                "`{}` is not a script, run the one in node_modules/.bin",
+                name
+            );
+            return Ok(Parser {
+                command: Command::ExecuteLocal,
+                args: Some([vec![name.to_string()], args.to_vec()].concat()),
+            });
+        }
+        Resolved::Candidates(candidates) => candidates,
+    };
+
+    match candidates.is_empty() {
+        // there are no scripts to check, like the default `npm start`
+        true if scripts.is_empty() => Ok(run(name.to_string())),
+        true => Err(CommonError::NotFound(format!(
+            "script `{}` not found in package.json or node_modules/.bin",
+            name
+        ))),
+        false => {
+            let choices = candidates
+                .iter()
+                .map(|candidate| format!("{} - {}", candidate, scripts[candidate]))
+                .collect::<Vec<String>>();
+            let message = format!("Script `{}` not found, did you mean", name);
+            Ok(run(utils::select_a_choice(&choices, "run", &message)?))
+        }
+    }
+}
+
+/// the last run script with its args in the project
+fn last_run() -> Result<Vec<String>, CommonError> {
+    scripts::read_history(Path::new(scripts::HISTORY_PATH))
+        .into_iter()
+        .next()
+        .ok_or_else(|| CommonError::NotFound("no script has been run by ri yet".to_string()))
+}
+
+fn to_agent(name: &str) -> Result<Agent, CommonError> {
+    match Agent::from(name.to_string()) {
+        Agent::None => Err(CommonError::Unsupported(format!(
+            "{} is not an agent, choose one of npm, yarn, pnpm and bun",
+            name
+        ))),
+        agent => Ok(agent),
+    }
+}
+
+/// the path relative to current directory, without the leading `./`
+fn display_path(path: &Path) -> String {
+    path.strip_prefix(".")
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn to_strings(strs: &[&str]) -> Vec<String> {
+    strs.iter().map(|s| s.to_string()).collect()
+}
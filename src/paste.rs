@@ -0,0 +1,220 @@
+use crate::error::CommonError;
+use clipboard::{ClipboardContext, ClipboardProvider};
+use std::{env, process::Command};
+
+/// read the text of the clipboard
+///
+/// the system clipboard is tried first, then the command line tools, and the OSC 52
+/// escape sequence at last, which works over SSH if the terminal supports it
+pub fn read_clipboard() -> Result<String, CommonError> {
+    let mut errors = vec![];
+
+    let readers: [fn() -> Result<String, CommonError>; 3] =
+        [read_by_provider, read_by_commands, osc52::read];
+    for reader in readers {
+        match reader() {
+            Ok(content) if !content.trim().is_empty() => return Ok(content.trim().to_string()),
+            Ok(_) => errors.push("clipboard is empty".to_string()),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+
+    Err(CommonError::ClipboardError(errors.join(", ")))
+}
+
+fn read_by_provider() -> Result<String, CommonError> {
+    let mut ctx: ClipboardContext =
+        ClipboardProvider::new().map_err(|err| CommonError::ClipboardError(err.to_string()))?;
+    ctx.get_contents()
+        .map_err(|err| CommonError::ClipboardError(err.to_string()))
+}
+
+fn read_by_commands() -> Result<String, CommonError> {
+    let mut commands = vec![
+        ("xclip", vec!["-selection", "clipboard", "-o"]),
+        ("xsel", vec!["--clipboard", "--output"]),
+        ("pbpaste", vec![]),
+        (
+            "powershell",
+            vec!["-NoProfile", "-Command", "Get-Clipboard"],
+        ),
+    ];
+    // prefer wl-paste in wayland sessions, xclip may read a stale clipboard from XWayland
+    let wl_paste = ("wl-paste", vec!["--no-newline"]);
+    match env::var_os("WAYLAND_DISPLAY") {
+        Some(_) => commands.insert(0, wl_paste),
+        None => commands.push(wl_paste),
+    }
+
+    for (program, args) in commands {
+        match Command::new(program).args(args).output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+            _ => continue,
+        }
+    }
+
+    Err(CommonError::ClipboardError(
+        "none of wl-paste, xclip, xsel, pbpaste is available".to_string(),
+    ))
+}
+
+#[cfg(unix)]
+mod osc52 {
+    use crate::error::CommonError;
+    use crossterm::terminal;
+    use std::{
+        io::{self, IsTerminal, Write},
+        time::{Duration, Instant},
+    };
+
+    /// terminals without OSC 52 support never reply
+    const TIMEOUT: Duration = Duration::from_millis(300);
+
+    /// ask the terminal for the clipboard by `ESC ] 52 ; c ; ? BEL`,
+    /// the reply is `ESC ] 52 ; c ; <base64> BEL` (or ended with `ESC \`)
+    pub fn read() -> Result<String, CommonError> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err(CommonError::ClipboardError(
+                "OSC 52 needs a terminal".to_string(),
+            ));
+        }
+
+        let reply = {
+            let _raw_mode = RawMode::enable()?;
+            let mut stdout = io::stdout();
+            stdout.write_all(b"\x1b]52;c;?\x07")?;
+            stdout.flush()?;
+            read_reply()?
+        };
+
+        let reply = String::from_utf8_lossy(&reply);
+        let encoded = reply
+            .trim_start_matches('\x1b')
+            .trim_end_matches(['\x07', '\\', '\x1b'])
+            .rsplit(';')
+            .next()
+            .unwrap_or_default();
+
+        let decoded = decode_base64(encoded).ok_or(CommonError::ClipboardError(
+            "OSC 52 reply is invalid".to_string(),
+        ))?;
+        Ok(String::from_utf8_lossy(&decoded).to_string())
+    }
+
+    /// the terminal is in raw mode while it lives, so the reply is read without `Enter` and
+    /// isn't echoed, and the mode is restored on any return or panic
+    struct RawMode;
+
+    impl RawMode {
+        fn enable() -> Result<RawMode, CommonError> {
+            terminal::enable_raw_mode()?;
+            Ok(RawMode)
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    /// read stdin byte by byte until the terminator, so the bytes after the reply are left
+    /// in stdin for the next prompt
+    ///
+    /// `io::Stdin` is buffered and would take them, and the terminal may never reply,
+    /// so the fd is polled with a timeout and read directly
+    fn read_reply() -> Result<Vec<u8>, CommonError> {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut reply = vec![];
+
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let mut fd = libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // one valid pollfd is passed
+            let ready = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                return Err(CommonError::ClipboardError(
+                    "terminal doesn't reply to OSC 52".to_string(),
+                ));
+            }
+
+            let mut byte = 0u8;
+            // at most one byte is written into `byte`
+            let read = unsafe { libc::read(libc::STDIN_FILENO, &mut byte as *mut u8 as *mut _, 1) };
+            if read <= 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            // the reply must start with ESC, otherwise it's the input of user
+            if reply.is_empty() && byte != 0x1b {
+                return Err(CommonError::ClipboardError(
+                    "terminal doesn't reply to OSC 52".to_string(),
+                ));
+            }
+            reply.push(byte);
+
+            if byte == 0x07 || reply.ends_with(b"\x1b\\") {
+                return Ok(reply);
+            }
+        }
+    }
+
+    /// the standard alphabet of the reply, small enough to not take a dependency for it
+    fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+        let mut decoded = vec![];
+        let mut buffer = 0u32;
+        let mut bits = 0;
+
+        for c in encoded.bytes().filter(|&c| c != b'=') {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return None,
+            };
+            buffer = (buffer << 6) | value as u32;
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                decoded.push((buffer >> bits) as u8);
+                buffer &= (1 << bits) - 1;
+            }
+        }
+
+        Some(decoded)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_decode_base64() {
+            assert_eq!(
+                decode_base64("aHR0cHM6Ly9naXRodWIuY29tL0ppYXRMbi9yaS5naXQ=").unwrap(),
+                b"https://github.com/JiatLn/ri.git"
+            );
+            assert_eq!(decode_base64("cmk=").unwrap(), b"ri");
+            assert_eq!(decode_base64("").unwrap(), b"");
+            assert_eq!(decode_base64("?"), None);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod osc52 {
+    use crate::error::CommonError;
+
+    pub fn read() -> Result<String, CommonError> {
+        Err(CommonError::ClipboardError(
+            "OSC 52 is only supported on unix".to_string(),
+        ))
+    }
+}
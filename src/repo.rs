@@ -0,0 +1,370 @@
+use crate::{
+    cargo_toml::{CargoLock, CargoToml},
+    config,
+    error::CommonError,
+    package_json::PackageJson,
+    utils,
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The page of a repository to open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoTarget {
+    Issues,
+    Pulls,
+    Actions,
+    Npm,
+    Docs,
+    Homepage,
+}
+
+impl FromStr for RepoTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "issues" => Ok(RepoTarget::Issues),
+            "pulls" => Ok(RepoTarget::Pulls),
+            "actions" => Ok(RepoTarget::Actions),
+            "npm" => Ok(RepoTarget::Npm),
+            "docs" => Ok(RepoTarget::Docs),
+            "homepage" => Ok(RepoTarget::Homepage),
+            _ => Err(format!(
+                "unknown target `{}`, expected one of issues, pulls, actions, npm, docs, homepage",
+                s
+            )),
+        }
+    }
+}
+
+/// The git hosting service of a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Host {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Other,
+}
+
+impl Host {
+    /// judge the host by the domain of a repository url
+    pub fn from_url(url: &str) -> Host {
+        let domain = url.split('/').nth(2).unwrap_or_default();
+        if domain.contains("github") {
+            Host::GitHub
+        } else if domain.contains("gitlab") {
+            Host::GitLab
+        } else if domain.contains("bitbucket") {
+            Host::Bitbucket
+        } else {
+            Host::Other
+        }
+    }
+
+    /// the path of a directory on the default branch
+    pub fn tree_path(&self, dir: &str) -> String {
+        match self {
+            Host::GitLab => format!("-/tree/HEAD/{}", dir),
+            Host::Bitbucket => format!("src/HEAD/{}", dir),
+            Host::GitHub | Host::Other => format!("tree/HEAD/{}", dir),
+        }
+    }
+
+    fn target_path(&self, target: RepoTarget) -> Option<&str> {
+        match (self, target) {
+            (Host::GitHub, RepoTarget::Issues) => Some("issues"),
+            (Host::GitHub, RepoTarget::Pulls) => Some("pulls"),
+            (Host::GitHub, RepoTarget::Actions) => Some("actions"),
+            (Host::GitLab, RepoTarget::Issues) => Some("-/issues"),
+            (Host::GitLab, RepoTarget::Pulls) => Some("-/merge_requests"),
+            (Host::GitLab, RepoTarget::Actions) => Some("-/pipelines"),
+            (Host::Bitbucket, RepoTarget::Issues) => Some("issues"),
+            (Host::Bitbucket, RepoTarget::Pulls) => Some("pull-requests"),
+            (Host::Bitbucket, RepoTarget::Actions) => Some("pipelines"),
+            _ => None,
+        }
+    }
+}
+
+/// get the repository url from `package.json` or `Cargo.toml` of the directory,
+/// the `repository` of a dependency crate takes precedence over its `homepage`
+pub fn get_repo_url(dir: &Path, is_dependency: bool) -> Result<String, CommonError> {
+    if dir.join("package.json").exists() {
+        PackageJson::from_path(dir.join("package.json"))?.get_url()
+    } else if dir.join("Cargo.toml").exists() {
+        let cargo_toml = CargoToml::from_path(dir.join("Cargo.toml"))?;
+        match is_dependency {
+            true => cargo_toml.get_repository_url(),
+            false => cargo_toml.get_url(),
+        }
+    } else {
+        Err(CommonError::NotFound(
+            "package.json or Cargo.toml not found!".to_string(),
+        ))
+    }
+}
+
+/// get the url of the target page, fields of `package.json` or `Cargo.toml` take precedence
+pub fn get_target_url(dir: &Path, target: RepoTarget) -> Result<String, CommonError> {
+    if dir.join("package.json").exists() {
+        let package_json = PackageJson::from_path(dir.join("package.json"))?;
+        match target {
+            RepoTarget::Npm => match package_json.name {
+                Some(name) => Ok(format!("https://www.npmjs.com/package/{}", name)),
+                None => Err(CommonError::NotFound(
+                    "package.json name field not found!".to_string(),
+                )),
+            },
+            RepoTarget::Homepage => match package_json.homepage {
+                Some(homepage) => Ok(homepage),
+                None => package_json.get_url(),
+            },
+            RepoTarget::Docs => match package_json.homepage {
+                Some(homepage) => Ok(homepage),
+                None => Ok(format!("{}#readme", package_json.get_url()?)),
+            },
+            RepoTarget::Issues => match package_json.get_bugs_url() {
+                Some(bugs) => Ok(bugs),
+                None => join_target(&package_json.get_root_url()?, target),
+            },
+            _ => join_target(&package_json.get_root_url()?, target),
+        }
+    } else if dir.join("Cargo.toml").exists() {
+        let cargo_toml = CargoToml::from_path(dir.join("Cargo.toml"))?;
+        match target {
+            RepoTarget::Npm => Ok(format!(
+                "https://crates.io/crates/{}",
+                cargo_toml.get_name()?
+            )),
+            RepoTarget::Homepage => cargo_toml.get_url(),
+            RepoTarget::Docs => match cargo_toml.get_documentation() {
+                Some(documentation) => Ok(documentation),
+                None => Ok(format!("https://docs.rs/{}", cargo_toml.get_name()?)),
+            },
+            _ => join_target(&cargo_toml.get_repository_url()?, target),
+        }
+    } else {
+        Err(CommonError::NotFound(
+            "package.json or Cargo.toml not found!".to_string(),
+        ))
+    }
+}
+
+/// find the directory of a dependency, in `node_modules` or the cargo registry
+pub fn find_dependency_dir(name: &str) -> Result<PathBuf, CommonError> {
+    if utils::is_cargo_project() {
+        return find_crate_dir(name);
+    }
+    find_node_module(&env::current_dir()?, name).ok_or(CommonError::NotFound(format!(
+        "{} not found in node_modules, is it installed?",
+        name
+    )))
+}
+
+/// `node_modules` of parent directories are searched too, dependencies may be hoisted in workspaces
+fn find_node_module(cwd: &Path, name: &str) -> Option<PathBuf> {
+    cwd.ancestors()
+        .map(|dir| dir.join("node_modules").join(name))
+        .find(|dir| dir.join("package.json").exists())
+}
+
+/// find the source of a crate in the cargo registry, by the version locked in `Cargo.lock`
+fn find_crate_dir(name: &str) -> Result<PathBuf, CommonError> {
+    let cwd = env::current_dir()?;
+    let cargo_lock = cwd
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.exists())
+        .ok_or(CommonError::NotFound(
+            "Cargo.lock not found, run `cargo fetch` first".to_string(),
+        ))?;
+    let versions = CargoLock::from_path(cargo_lock)?.get_registry_versions(name);
+
+    let cargo_home = match env::var_os("CARGO_HOME") {
+        Some(cargo_home) => PathBuf::from(cargo_home),
+        None => config::home_dir()
+            .ok_or(CommonError::NotFound(
+                "home directory not found".to_string(),
+            ))?
+            .join(".cargo"),
+    };
+
+    // every registry has its own directory, like `index.crates.io-6f17d22bba15001f`
+    let registries = fs::read_dir(cargo_home.join("registry").join("src"))?;
+    registries
+        .filter_map(|registry| registry.ok())
+        .flat_map(|registry| {
+            versions
+                .iter()
+                .map(move |version| registry.path().join(format!("{}-{}", name, version)))
+        })
+        .find(|dir| dir.join("Cargo.toml").exists())
+        .ok_or(CommonError::NotFound(format!(
+            "{} not found in the cargo registry, is it a dependency?",
+            name
+        )))
+}
+
+/// convert the source of `ri cl` to a clone url
+///
+/// such as `user/repo`, `github:user/repo` or `https://github.com/user/repo/tree/main`
+pub fn to_clone_url(src: &str) -> Option<String> {
+    let src = src.trim();
+    if src.is_empty() || src.contains(char::is_whitespace) {
+        return None;
+    }
+
+    // ssh and git protocols aren't copied from the browser, keep them as is
+    if src.starts_with("git@") || src.starts_with("ssh://") || src.starts_with("git://") {
+        return Some(src.to_string());
+    }
+
+    let (scheme, host, repo_path) = match src.split_once("://") {
+        Some((scheme, rest)) => {
+            let rest = rest.split(['?', '#']).next()?;
+            let (host, path) = rest.split_once('/')?;
+            // drop the page of the repository, like `/tree/main` or `/-/blob/main/README.md`
+            let repo_path = match Host::from_url(src) {
+                Host::GitLab => path.split("/-/").next()?.to_string(),
+                Host::GitHub | Host::Bitbucket => {
+                    path.split('/').take(2).collect::<Vec<&str>>().join("/")
+                }
+                Host::Other => path.to_string(),
+            };
+            (scheme, host, repo_path)
+        }
+        None => match src.split_once(':') {
+            Some(("github", path)) => ("https", "github.com", path.to_string()),
+            Some(("gitlab", path)) => ("https", "gitlab.com", path.to_string()),
+            Some(("bitbucket", path)) => ("https", "bitbucket.org", path.to_string()),
+            Some(_) => return None,
+            None if src.split('/').count() == 2 => ("https", "github.com", src.to_string()),
+            None => return None,
+        },
+    };
+
+    let repo_path = repo_path.trim_matches('/');
+    let repo_path = repo_path.strip_suffix(".git").unwrap_or(repo_path);
+    if repo_path.split('/').filter(|s| !s.is_empty()).count() < 2 {
+        return None;
+    }
+
+    Some(format!("{}://{}/{}.git", scheme, host, repo_path))
+}
+
+/// the directory `git clone` creates, like `repo` of `git@github.com:user/repo.git`
+pub fn get_repo_name(clone_url: &str) -> Option<String> {
+    let name = clone_url.trim_end_matches('/').rsplit(['/', ':']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    match name.is_empty() {
+        true => None,
+        false => Some(name.to_string()),
+    }
+}
+
+fn join_target(repo_url: &str, target: RepoTarget) -> Result<String, CommonError> {
+    match Host::from_url(repo_url).target_path(target) {
+        Some(path) => Ok(format!("{}/{}", repo_url.trim_end_matches('/'), path)),
+        None => Err(CommonError::Unsupported(format!(
+            "don't know the {:?} page of {}",
+            target, repo_url
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_target() {
+        assert_eq!(
+            join_target("https://github.com/JiatLn/ri", RepoTarget::Pulls).unwrap(),
+            "https://github.com/JiatLn/ri/pulls"
+        );
+        assert_eq!(
+            join_target("https://gitlab.com/user/repo", RepoTarget::Pulls).unwrap(),
+            "https://gitlab.com/user/repo/-/merge_requests"
+        );
+        assert_eq!(
+            join_target("https://bitbucket.org/user/repo", RepoTarget::Actions).unwrap(),
+            "https://bitbucket.org/user/repo/pipelines"
+        );
+        assert!(join_target("https://git.example.com/user/repo", RepoTarget::Issues).is_err());
+    }
+
+    #[test]
+    fn test_to_clone_url() {
+        let expected = "https://github.com/JiatLn/ri.git";
+        for src in [
+            "JiatLn/ri",
+            "github:JiatLn/ri",
+            "https://github.com/JiatLn/ri",
+            "https://github.com/JiatLn/ri.git",
+            "https://github.com/JiatLn/ri/",
+            "https://github.com/JiatLn/ri/tree/main/src",
+            "https://github.com/JiatLn/ri?tab=readme-ov-file#ri",
+        ] {
+            assert_eq!(to_clone_url(src).unwrap(), expected, "{}", src);
+        }
+
+        assert_eq!(
+            to_clone_url("https://gitlab.com/group/sub/repo/-/tree/main").unwrap(),
+            "https://gitlab.com/group/sub/repo.git"
+        );
+        assert_eq!(
+            to_clone_url("bitbucket:user/repo").unwrap(),
+            "https://bitbucket.org/user/repo.git"
+        );
+        assert_eq!(
+            to_clone_url("git@github.com:JiatLn/ri.git").unwrap(),
+            "git@github.com:JiatLn/ri.git"
+        );
+        for src in ["", "ri", "https://github.com/JiatLn", "npm:ri", "a b/c"] {
+            assert_eq!(to_clone_url(src), None, "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_get_repo_name() {
+        assert_eq!(
+            get_repo_name("https://github.com/JiatLn/ri.git").unwrap(),
+            "ri"
+        );
+        assert_eq!(get_repo_name("git@github.com:JiatLn/ri.git").unwrap(), "ri");
+        assert_eq!(get_repo_name("git@host:ri.git").unwrap(), "ri");
+    }
+
+    #[test]
+    fn test_find_node_module() {
+        let root = tempfile::tempdir().unwrap();
+        let hoisted = root.path().join("node_modules").join("react");
+        let nested = root.path().join("packages").join("app");
+        fs::create_dir_all(&hoisted).unwrap();
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(hoisted.join("package.json"), "{}").unwrap();
+
+        assert_eq!(find_node_module(&nested, "react"), Some(hoisted));
+        assert_eq!(find_node_module(&nested, "vue"), None);
+    }
+
+    #[test]
+    fn test_get_repo_url_of_crate() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"serde\"\nhomepage = \"https://serde.rs\"\nrepository = \"https://github.com/serde-rs/serde\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(get_repo_url(dir.path(), false).unwrap(), "https://serde.rs");
+        assert_eq!(
+            get_repo_url(dir.path(), true).unwrap(),
+            "https://github.com/serde-rs/serde"
+        );
+    }
+}
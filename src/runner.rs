@@ -1,5 +1,12 @@
 use crate::error::CommonError;
-use std::process::Command;
+use std::{
+    io::{self, BufRead, BufReader, IsTerminal, Read, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+/// colors of the prefixes of parallel commands, cyan, magenta, yellow, green, blue and red
+const COLORS: [u8; 6] = [36, 35, 33, 32, 34, 31];
 
 pub struct Runner {
     executor: Executor,
@@ -7,26 +14,142 @@ pub struct Runner {
 
 impl Runner {
     pub fn run(cmd: &str) -> Result<(), CommonError> {
-        if cmd.trim().len() == 0 {
+        if cmd.trim().is_empty() {
             return Ok(());
         }
 
-        let args = cmd
-            .split(" ")
-            .map(|s| String::from(s))
-            .collect::<Vec<String>>();
-
         let mut runner = Self::new();
 
-        runner
-            .executor
-            .command
-            .arg(runner.executor.arg_0)
-            .args(args)
-            .spawn()?
-            .wait()?;
+        // the whole command is handed to the shell, `sh -c npm run dev` would run
+        // `npm` only and take `run` and `dev` as positional parameters of the shell
+        runner.executor.command.arg(&runner.executor.arg_0);
+        runner.pass_command(cmd);
+        let status = runner.executor.command.spawn()?.wait()?;
 
-        Ok(())
+        match status.success() {
+            true => Ok(()),
+            false => Err(CommonError::ExecuteFailed(format!(
+                "`{}` exited with {}",
+                cmd, status
+            ))),
+        }
+    }
+}
+
+impl Runner {
+    /// run the named commands at the same time, each line of the output is prefixed with
+    /// the name, Ctrl+C stops all of them and ri waits for them to exit
+    pub fn run_parallel(commands: &[(String, String)]) -> Result<(), CommonError> {
+        let width = commands
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let is_terminal = io::stdout().is_terminal();
+
+        let mut children = vec![];
+        for (index, (name, cmd)) in commands.iter().enumerate() {
+            let mut runner = Self::new();
+            runner.executor.command.arg(&runner.executor.arg_0);
+            runner.pass_command(cmd);
+            // the output is piped, keep the colors of the tools
+            if is_terminal {
+                runner.executor.command.env("FORCE_COLOR", "1");
+            }
+            let child = runner
+                .executor
+                .command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+
+            let prefix = match is_terminal {
+                true => format!(
+                    "\x1b[{}m{:<width$} |\x1b[0m",
+                    COLORS[index % COLORS.len()],
+                    name,
+                    width = width
+                ),
+                false => format!("{:<width$} |", name, width = width),
+            };
+            children.push((cmd, prefix, child));
+        }
+
+        let outputs = children
+            .iter_mut()
+            .map(|(_, prefix, child)| (prefix.clone(), child.stdout.take(), child.stderr.take()))
+            .collect::<Vec<_>>();
+
+        // the children are spawned, they still stop by Ctrl+C
+        let _interrupt = IgnoreInterrupt::new();
+        let failures = thread::scope(|scope| {
+            for (prefix, stdout, stderr) in outputs {
+                if let Some(stderr) = stderr {
+                    let prefix = prefix.clone();
+                    scope.spawn(move || print_lines(stderr, &prefix, io::stderr()));
+                }
+                if let Some(stdout) = stdout {
+                    scope.spawn(move || print_lines(stdout, &prefix, io::stdout()));
+                }
+            }
+
+            let mut failures = vec![];
+            for (cmd, _, child) in children.iter_mut() {
+                match child.wait() {
+                    Ok(status) if status.success() => {}
+                    Ok(status) => failures.push(format!("`{}` exited with {}", cmd, status)),
+                    Err(err) => failures.push(format!("`{}` failed: {}", cmd, err)),
+                }
+            }
+            failures
+        });
+
+        match failures.is_empty() {
+            true => Ok(()),
+            false => Err(CommonError::ExecuteFailed(failures.join(", "))),
+        }
+    }
+}
+
+/// lines are read as bytes, the output of commands isn't always valid utf-8
+fn print_lines<R: Read, W: Write>(reader: R, prefix: &str, mut writer: W) {
+    let mut reader = BufReader::new(reader);
+    let mut line = vec![];
+    while let Ok(size) = reader.read_until(b'\n', &mut line) {
+        if size == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&line);
+        let _ = writeln!(writer, "{} {}", prefix, text.trim_end_matches(['\r', '\n']));
+        line.clear();
+    }
+}
+
+/// ignore Ctrl+C until dropped, so ri outlives the commands and reports them
+struct IgnoreInterrupt {
+    #[cfg(unix)]
+    previous: libc::sighandler_t,
+}
+
+impl IgnoreInterrupt {
+    #[cfg(unix)]
+    fn new() -> IgnoreInterrupt {
+        let previous = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+        IgnoreInterrupt { previous }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> IgnoreInterrupt {
+        IgnoreInterrupt {}
+    }
+}
+
+impl Drop for IgnoreInterrupt {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, self.previous);
+        }
     }
 }
 
@@ -50,4 +173,16 @@ impl Runner {
         };
         Runner { executor }
     }
+
+    #[cfg(target_os = "windows")]
+    fn pass_command(&mut self, cmd: &str) {
+        use std::os::windows::process::CommandExt;
+        // cmd has its own quoting rules, so don't let std quote the command again
+        self.executor.command.raw_arg(cmd);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn pass_command(&mut self, cmd: &str) {
+        self.executor.command.arg(cmd);
+    }
 }
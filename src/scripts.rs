@@ -0,0 +1,317 @@
+use crate::{error::CommonError, package_json::PackageJson};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// the runs of scripts in the project, kept in node_modules so git ignores it
+pub const HISTORY_PATH: &str = "node_modules/.cache/ri/history";
+
+const HISTORY_SIZE: usize = 50;
+
+/// the runs of scripts, the latest first, each is the script name with its args
+pub fn read_history(path: &Path) -> Vec<Vec<String>> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// move the run to the front of the history, nothing is recorded before node_modules
+/// is installed, ri doesn't create it
+pub fn record_history(path: &Path, run: &[String]) -> Result<(), CommonError> {
+    let is_installed = path
+        .ancestors()
+        .any(|dir| dir.ends_with("node_modules") && dir.is_dir());
+    if !is_installed || run.is_empty() {
+        return Ok(());
+    }
+
+    let mut history = read_history(path);
+    history.retain(|item| item != run);
+    history.insert(0, run.to_vec());
+    history.truncate(HISTORY_SIZE);
+
+    let lines = history
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<String>, _>>()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// the recently run scripts first, then the others by name, a run is recorded like
+/// `test --watch`, `lint test` or `-p dev:api dev:web`, and its first script counts
+pub fn sort_scripts(mut names: Vec<String>, history: &[Vec<String>]) -> Vec<String> {
+    let recency = |name: &String| {
+        history
+            .iter()
+            .position(|run| run.iter().find(|arg| *arg != "-p") == Some(name))
+            .unwrap_or(history.len())
+    };
+    names.sort_by(|a, b| recency(a).cmp(&recency(b)).then(a.cmp(b)));
+    names
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ScriptInfo {
+    pub name: String,
+    pub command: String,
+    pub description: Option<String>,
+}
+
+/// the scripts of the package sorted by name, with their descriptions
+pub fn get_script_infos(package_json: &PackageJson) -> Vec<ScriptInfo> {
+    let mut infos = package_json
+        .scripts
+        .iter()
+        .flatten()
+        .map(|(name, command)| ScriptInfo {
+            name: name.to_string(),
+            command: command.to_string(),
+            description: package_json.get_script_description(name),
+        })
+        .collect::<Vec<ScriptInfo>>();
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    infos
+}
+
+/// the script with the name, or the only one starting with it, otherwise the candidates
+/// are returned, which are the ones starting with it or the fuzzy matched ones
+pub fn resolve_script(name: &str, names: &[String]) -> Result<String, Vec<String>> {
+    if names.iter().any(|script| script == name) {
+        return Ok(name.to_string());
+    }
+
+    let mut prefixed = names
+        .iter()
+        .filter(|script| script.starts_with(name))
+        .cloned()
+        .collect::<Vec<String>>();
+    prefixed.sort();
+    match prefixed.len() {
+        1 => return Ok(prefixed.remove(0)),
+        0 => {}
+        _ => return Err(prefixed),
+    }
+
+    let mut matched = names
+        .iter()
+        .filter_map(|script| fuzzy_score(name, script).map(|score| (score, script)))
+        .collect::<Vec<(usize, &String)>>();
+    matched.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+    Err(matched
+        .into_iter()
+        .map(|(_, script)| script.clone())
+        .collect())
+}
+
+/// what a name given to `ri r` runs
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resolved {
+    Script(String),
+    Bin(PathBuf),
+    /// the scripts possibly meant, empty if none is similar
+    Candidates(Vec<String>),
+}
+
+/// the script with the name, then the binary of dependencies with it, then the only script
+/// starting with it, so `eslint` isn't taken as a prefix of the `eslint:fix` script
+pub fn resolve_run(name: &str, names: &[String], dir: &Path) -> Resolved {
+    if names.iter().any(|script| script == name) {
+        return Resolved::Script(name.to_string());
+    }
+    if let Some(bin) = find_bin(name, dir) {
+        return Resolved::Bin(bin);
+    }
+    match resolve_script(name, names) {
+        Ok(script) => Resolved::Script(script),
+        Err(candidates) => Resolved::Candidates(candidates),
+    }
+}
+
+/// the binary of a dependency in `node_modules/.bin`, the parent directories are looked up
+/// too, where the root of workspaces is
+pub fn find_bin(name: &str, dir: &Path) -> Option<PathBuf> {
+    let file_names = match cfg!(target_os = "windows") {
+        true => vec![format!("{}.cmd", name), name.to_string()],
+        false => vec![name.to_string()],
+    };
+    dir.ancestors().find_map(|dir| {
+        file_names
+            .iter()
+            .map(|file_name| dir.join("node_modules/.bin").join(file_name))
+            .find(|path| path.is_file())
+    })
+}
+
+/// the chars of pattern must appear in the name in order, case insensitive,
+/// consecutive chars and the ones at the start of words score higher
+pub fn fuzzy_score(pattern: &str, name: &str) -> Option<usize> {
+    let name = name.to_lowercase().chars().collect::<Vec<char>>();
+    let mut score = 0;
+    let mut index = 0;
+    let mut previous = None;
+
+    for c in pattern.to_lowercase().chars() {
+        let found = index + name[index..].iter().position(|&n| n == c)?;
+        score += 1;
+        if previous.map_or(found == 0, |previous| found == previous + 1) {
+            score += 2;
+        }
+        if found == 0 || [':', '-', '_', '.', ' '].contains(&name[found - 1]) {
+            score += 1;
+        }
+        previous = Some(found);
+        index = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("node_modules/.cache/ri/history");
+        let run = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        // dependencies are not installed
+        record_history(&path, &run(&["dev"])).unwrap();
+        assert!(read_history(&path).is_empty());
+
+        fs::create_dir(root.path().join("node_modules")).unwrap();
+        record_history(&path, &run(&["dev"])).unwrap();
+        record_history(&path, &run(&["test", "--watch"])).unwrap();
+        record_history(&path, &run(&["dev"])).unwrap();
+        assert_eq!(
+            read_history(&path),
+            vec![run(&["dev"]), run(&["test", "--watch"])]
+        );
+
+        let names = run(&["build", "lint", "test", "dev"]);
+        assert_eq!(
+            sort_scripts(names.clone(), &read_history(&path)),
+            run(&["dev", "test", "build", "lint"])
+        );
+
+        record_history(&path, &run(&["-p", "lint", "build"])).unwrap();
+        assert_eq!(
+            sort_scripts(names, &read_history(&path)),
+            run(&["lint", "dev", "test", "build"])
+        );
+    }
+
+    #[test]
+    fn test_get_script_infos() {
+        let package_json = PackageJson::from_path("tests/package.json").unwrap();
+        let infos = get_script_infos(&package_json);
+        assert_eq!(
+            infos
+                .iter()
+                .map(|info| &info.name)
+                .collect::<Vec<&String>>(),
+            vec!["build", "dev", "test"]
+        );
+        assert_eq!(
+            infos[1],
+            ScriptInfo {
+                name: "dev".to_string(),
+                command: "vite".to_string(),
+                description: Some("start the dev server".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_script() {
+        let names = ["build", "dev", "dev:web", "test", "test:e2e"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(resolve_script("dev", &names), Ok("dev".to_string()));
+        assert_eq!(resolve_script("bu", &names), Ok("build".to_string()));
+        assert_eq!(
+            resolve_script("tes", &names),
+            Err(vec!["test".to_string(), "test:e2e".to_string()])
+        );
+        assert_eq!(
+            resolve_script("tst", &names),
+            Err(vec!["test".to_string(), "test:e2e".to_string()])
+        );
+        assert_eq!(resolve_script("lint", &names), Err(vec![]));
+    }
+
+    #[test]
+    fn test_find_bin() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("node_modules/.bin")).unwrap();
+        fs::create_dir_all(root.join("packages/core/node_modules/.bin")).unwrap();
+        fs::write(root.join("node_modules/.bin/eslint"), "").unwrap();
+        fs::write(root.join("packages/core/node_modules/.bin/vite"), "").unwrap();
+
+        let dir = root.join("packages/core");
+        assert_eq!(
+            find_bin("eslint", &dir),
+            Some(root.join("node_modules/.bin/eslint"))
+        );
+        assert_eq!(
+            find_bin("vite", &dir),
+            Some(dir.join("node_modules/.bin/vite"))
+        );
+        assert_eq!(find_bin("vite", root), None);
+        assert_eq!(find_bin("tsc", &dir), None);
+    }
+
+    #[test]
+    fn test_resolve_run() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("node_modules/.bin")).unwrap();
+        fs::write(root.join("node_modules/.bin/eslint"), "").unwrap();
+        let names = ["eslint:fix", "lint"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            resolve_run("eslint", &names, root),
+            Resolved::Bin(root.join("node_modules/.bin/eslint"))
+        );
+        assert_eq!(
+            resolve_run("esl", &names, root),
+            Resolved::Script("eslint:fix".to_string())
+        );
+        assert_eq!(
+            resolve_run("lnt", &names, root),
+            Resolved::Candidates(vec!["lint".to_string(), "eslint:fix".to_string()])
+        );
+
+        let names = [names, vec!["eslint".to_string()]].concat();
+        assert_eq!(
+            resolve_run("eslint", &names, root),
+            Resolved::Script("eslint".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "dev"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "dev"), None);
+        assert_eq!(fuzzy_score("ved", "dev"), None);
+        assert!(fuzzy_score("tw", "test:watch").is_some());
+        assert!(fuzzy_score("DEV", "dev").is_some());
+        assert!(fuzzy_score("dev", "dev:web") > fuzzy_score("dev", "deploy:v2"));
+        assert!(fuzzy_score("bu", "build") > fuzzy_score("bu", "docs:build"));
+    }
+}
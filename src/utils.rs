@@ -1,6 +1,56 @@
-use crate::{agents::Agents, error::CommonError};
+use crate::{
+    error::CommonError,
+    repo::{self, Host},
+    scripts,
+};
 use requestty::{ListItem, OnEsc, Question};
-use std::{fs, path::Path, process};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, IsTerminal},
+    path::Path,
+    process,
+    sync::OnceLock,
+};
+
+/// how the prompts are answered, confirms take yes or their default without prompting,
+/// and the others fail without a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interaction {
+    Prompt,
+    Yes,
+    Default,
+}
+
+static INTERACTION: OnceLock<Interaction> = OnceLock::new();
+
+/// set by the global flags, prompts are never shown if stdin isn't a terminal, like in CI
+pub fn init_interaction(yes: bool, no_interactive: bool) {
+    let interaction = match (yes, no_interactive) {
+        (true, _) => Interaction::Yes,
+        (_, true) => Interaction::Default,
+        _ if !io::stdin().is_terminal() => Interaction::Default,
+        _ => Interaction::Prompt,
+    };
+    let _ = INTERACTION.set(interaction);
+}
+
+fn interaction() -> Interaction {
+    INTERACTION.get().copied().unwrap_or(Interaction::Prompt)
+}
+
+/// selects and inputs have no answer to take, `--yes` still shows them in a terminal
+fn can_prompt() -> bool {
+    interaction() != Interaction::Default && io::stdin().is_terminal()
+}
+
+fn not_interactive(message: &str, hint: &str) -> CommonError {
+    CommonError::NotInteractive(format!(
+        "`{}` needs an interactive terminal, {}",
+        message, hint
+    ))
+}
 
 pub fn exclude(args: Vec<String>, v: &str) -> Vec<String> {
     args.into_iter()
@@ -13,6 +63,15 @@ pub fn select_a_choice(
     name: &str,
     message: &str,
 ) -> Result<String, CommonError> {
+    if !can_prompt() {
+        let choices = vec_choices
+            .iter()
+            .map(|choice| choice.split(" - ").next().unwrap_or_default())
+            .collect::<Vec<&str>>();
+        let hint = format!("the choices are: {}", choices.join(", "));
+        return Err(not_interactive(message, &hint));
+    }
+
     let select = Question::select(name)
         .message(message)
         .choices(vec_choices)
@@ -37,21 +96,105 @@ pub fn select_a_choice(
     }
 }
 
-pub fn remove_dir_all_file_with_path<P: AsRef<Path>>(path: P) -> Result<(), CommonError> {
-    fs::remove_dir_all(path)?;
-    Ok(())
+/// the last choice of `select_a_fuzzy_choice`, to type a pattern narrowing the list down
+const FILTER_CHOICE: &str = "/filter";
+
+/// like `select_a_choice`, the choices are listed in their order, and the last one
+/// filters them by a fuzzy matched pattern
+pub fn select_a_fuzzy_choice(
+    vec_choices: &[String],
+    name: &str,
+    message: &str,
+) -> Result<String, CommonError> {
+    if !can_prompt() {
+        return select_a_choice(&vec_choices.to_vec(), name, message);
+    }
+
+    let mut choices = vec_choices.to_vec();
+    loop {
+        let mut list = choices.clone();
+        list.push(format!("{} - type to narrow the list down", FILTER_CHOICE));
+        let answer = select_a_choice(&list, name, message)?;
+        if answer != FILTER_CHOICE {
+            return Ok(answer);
+        }
+
+        let pattern = ask_input_question("Filter", |_| Ok(()))?;
+        choices = filter_choices(vec_choices, &pattern);
+        if choices.is_empty() {
+            println!("nothing matches `{}`", pattern);
+            choices = vec_choices.to_vec();
+        }
+    }
+}
+
+/// the choices whose names fuzzy match the pattern, the better matched first,
+/// and in their order for the same score
+fn filter_choices(choices: &[String], pattern: &str) -> Vec<String> {
+    let mut matched = choices
+        .iter()
+        .filter_map(|choice| {
+            let name = choice.split(" - ").next().unwrap_or_default();
+            scripts::fuzzy_score(pattern, name).map(|score| (score, choice))
+        })
+        .collect::<Vec<(usize, &String)>>();
+    // the sort is stable
+    matched.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matched
+        .into_iter()
+        .map(|(_, choice)| choice.clone())
+        .collect()
 }
 
-pub fn remove_lock_files() -> Result<(), CommonError> {
-    for (file_name, _) in Agents::new().lock_map {
-        if fs::read(&file_name).is_ok() {
+pub fn remove_lock_files(lock_files: &[String]) -> Result<(), CommonError> {
+    for file_name in lock_files {
+        if fs::read(file_name).is_ok() {
             fs::remove_file(file_name)?;
         }
     }
     Ok(())
 }
 
+pub fn ask_input_question<F>(question_content: &str, validate: F) -> Result<String, CommonError>
+where
+    F: Fn(&str) -> Result<(), String>,
+{
+    if !can_prompt() {
+        return Err(not_interactive(
+            question_content,
+            "pass it as an argument instead",
+        ));
+    }
+
+    let input = Question::input("q")
+        .message(question_content)
+        .validate(|answer, _| validate(answer))
+        .build();
+
+    let answer = requestty::prompt_one(input)?;
+
+    match answer.as_string() {
+        Some(s) => Ok(s.trim().to_string()),
+        None => process::exit(1),
+    }
+}
+
+/// answered with no by default, or yes with `--yes`
 pub fn ask_confirm_question(question_content: &str) -> Result<bool, CommonError> {
+    let answer = match interaction() {
+        Interaction::Prompt => None,
+        Interaction::Yes => Some(true),
+        Interaction::Default => Some(false),
+    };
+    if let Some(answer) = answer {
+        let answer_text = match answer {
+            true => "yes",
+            false => "no",
+        };
+        println!("? {} {}", question_content, answer_text);
+        return Ok(answer);
+    }
+
     let confirm = Question::confirm("q")
         .message(question_content)
         .default(false)
@@ -67,7 +210,70 @@ pub fn ask_confirm_question(question_content: &str) -> Result<bool, CommonError>
 
 /// judge content is a git clone url or not.
 ///
-/// such as `git@xxx/xxx/xxx.git` or `http(s)://xxx/xxx/xxx.git`
+/// such as `git@xxx:xxx/xxx.git`, `http(s)://xxx/xxx/xxx.git` or a page of a repository
+/// on GitHub, GitLab or Bitbucket, the other links aren't taken as repositories
 pub fn is_a_git_clone_url(ctx: &str) -> bool {
-    ctx.ends_with(".git") && (ctx.starts_with("http") || ctx.starts_with("git@"))
+    let ctx = ctx.trim();
+    let is_http = (ctx.starts_with("http://") || ctx.starts_with("https://"))
+        && (ctx.ends_with(".git") || Host::from_url(ctx) != Host::Other);
+    let is_ssh = ["git@", "ssh://", "git://"]
+        .iter()
+        .any(|prefix| ctx.starts_with(prefix));
+    (is_http || is_ssh) && repo::to_clone_url(ctx).is_some()
+}
+
+/// judge current directory is a rust project or not.
+///
+/// a directory with both `package.json` and `Cargo.toml` is treated as a node project
+pub fn is_cargo_project() -> bool {
+    !Path::new("package.json").exists() && Path::new("Cargo.toml").exists()
+}
+
+/// quote an arg for the shell of `Runner` if needed, like `--author='John Doe'`
+pub fn quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_=./:@,+".contains(c));
+    if is_safe {
+        arg.to_string()
+    } else if cfg!(target_os = "windows") {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// hash the contents of files, a missing file is hashed as empty
+pub fn hash_files<P: AsRef<Path>>(paths: &[P]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for path in paths {
+        path.as_ref().hash(&mut hasher);
+        fs::read(path).ok().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_a_git_clone_url() {
+        for ctx in [
+            "https://github.com/JiatLn/ri",
+            "https://gitlab.com/group/repo/-/tree/main",
+            "https://git.example.com/user/repo.git",
+            "git@github.com:JiatLn/ri.git",
+        ] {
+            assert!(is_a_git_clone_url(ctx), "{}", ctx);
+        }
+        for ctx in [
+            "https://stackoverflow.com/questions/123/title",
+            "https://github.com/JiatLn",
+            "JiatLn/ri",
+        ] {
+            assert!(!is_a_git_clone_url(ctx), "{}", ctx);
+        }
+    }
 }
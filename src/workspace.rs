@@ -0,0 +1,206 @@
+use crate::package_json::{PackageJson, PkgWorkspaces};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// packages of the workspace in the directory, declared by `workspaces` of package.json
+/// or `pnpm-workspace.yaml`
+pub fn get_packages(root: &Path) -> Vec<PackageJson> {
+    let mut patterns = match PackageJson::from_path(root.join("package.json")) {
+        Ok(PackageJson {
+            workspaces: Some(workspaces),
+            ..
+        }) => match workspaces {
+            PkgWorkspaces::Array(patterns) => patterns,
+            PkgWorkspaces::Object { packages } => packages,
+        },
+        _ => vec![],
+    };
+    if let Ok(contents) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        patterns.extend(parse_pnpm_workspace(&contents));
+    }
+
+    let mut paths = patterns
+        .iter()
+        // negated patterns are used to exclude test fixtures, which are not packages anyway
+        .filter(|pattern| !pattern.starts_with('!'))
+        .flat_map(|pattern| expand_pattern(root, pattern))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter_map(|path| PackageJson::from_path(path.join("package.json")).ok())
+        .collect()
+}
+
+/// the items of `packages` in `pnpm-workspace.yaml`
+///
+/// ```yaml
+/// packages:
+///   - 'packages/*'
+///   - "apps/**"
+/// ```
+fn parse_pnpm_workspace(contents: &str) -> Vec<String> {
+    let mut patterns = vec![];
+    let mut in_packages = false;
+
+    for line in contents.lines() {
+        let item = line.split(" #").next().unwrap_or_default().trim();
+        if item.is_empty() {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = item.starts_with("packages:");
+            continue;
+        }
+        if let (true, Some(pattern)) = (in_packages, item.strip_prefix('-')) {
+            patterns.push(pattern.trim().trim_matches(['\'', '"']).to_string());
+        }
+    }
+
+    patterns
+}
+
+/// directories with a package.json matching the pattern, `*` and `**` are supported
+fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let segments = pattern
+        .trim_start_matches("./")
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<&str>>();
+
+    let mut paths = vec![];
+    walk(root, &segments, &mut paths);
+    paths
+        .into_iter()
+        .filter(|path| path.join("package.json").is_file())
+        .collect()
+}
+
+fn walk(dir: &Path, segments: &[&str], paths: &mut Vec<PathBuf>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(first) => first,
+        None => {
+            paths.push(dir.to_path_buf());
+            return;
+        }
+    };
+
+    if !segment.contains('*') {
+        let path = dir.join(segment);
+        if path.is_dir() {
+            walk(&path, rest, paths);
+        }
+        return;
+    }
+
+    if *segment == "**" {
+        // `**` matches zero or more directories
+        walk(dir, rest, paths);
+    }
+    for sub_dir in sub_dirs(dir) {
+        let name = sub_dir.file_name().unwrap_or_default().to_string_lossy();
+        if *segment == "**" {
+            walk(&sub_dir, segments, paths);
+        } else if matches_segment(segment, &name) {
+            walk(&sub_dir, rest, paths);
+        }
+    }
+}
+
+/// sub directories, except `node_modules` and hidden ones, symlinks are not followed
+fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name != "node_modules" && !name.starts_with('.')
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// match a name with a pattern like `*`, `plugin-*` or `*-utils`
+fn matches_segment(pattern: &str, name: &str) -> bool {
+    let parts = pattern.split('*').collect::<Vec<&str>>();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pnpm_workspace() {
+        let contents = r#"
+packages:
+  # all packages in direct subdirs of packages/
+  - 'packages/*'
+  - "apps/**"
+  - components
+  - '!**/test/**'
+catalog:
+  - react
+"#;
+        assert_eq!(
+            parse_pnpm_workspace(contents),
+            vec!["packages/*", "apps/**", "components", "!**/test/**"]
+        );
+    }
+
+    #[test]
+    fn test_matches_segment() {
+        assert!(matches_segment("*", "core"));
+        assert!(matches_segment("plugin-*", "plugin-vue"));
+        assert!(matches_segment("*-utils", "shared-utils"));
+        assert!(!matches_segment("plugin-*", "core"));
+        assert!(!matches_segment("a*a", "a"));
+    }
+
+    #[test]
+    fn test_get_packages() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for dir in ["packages/core", "packages/utils", "apps/web/site", "docs"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            let name = dir.rsplit('/').next().unwrap();
+            fs::write(
+                root.join(dir).join("package.json"),
+                format!("{{\"name\": \"{}\"}}", name),
+            )
+            .unwrap();
+        }
+        fs::write(
+            root.join("package.json"),
+            r#"{"workspaces": ["packages/*", "apps/**"]}"#,
+        )
+        .unwrap();
+
+        let names = get_packages(root)
+            .into_iter()
+            .map(|pkg| pkg.name.unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!["site", "core", "utils"]);
+    }
+}